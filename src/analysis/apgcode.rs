@@ -0,0 +1,183 @@
+//! apgcodes name a pattern by the kind of object it is and its cells in extended Wechsler format,
+//! e.g. `xs4_33` for the block, `xp2_7` for the blinker and `xq4_153` for the glider.
+
+use crate::{args::GOLMethod, pattern::Pattern, GOLError};
+
+use super::census::{Kind, Object};
+
+/// Characters for the columns of a strip, each a 5 bit number with the top row as the lowest bit
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuv";
+/// Characters following `y` for runs of 4 to 39 empty columns
+const RUN_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Encodes a pattern in extended Wechsler format: the pattern is cut into strips 5 rows high
+/// separated by `z`, each column is a character and runs of empty columns are shortened with
+/// `w`, `x` and `y`
+pub fn wechsler(pattern: &Pattern) -> String {
+    let mut strips = Vec::new();
+    for top in (0..pattern.height).step_by(5) {
+        let mut columns: Vec<u8> = (0..pattern.width)
+            .map(|x| {
+                (0..5)
+                    .filter(|row| top + row < pattern.height && pattern.get(x, top + row))
+                    .fold(0, |acc, row| acc | 1 << row)
+            })
+            .collect();
+        while columns.last() == Some(&0) {
+            columns.pop();
+        }
+
+        let mut strip = String::new();
+        let mut empty = 0;
+        for column in columns {
+            if column == 0 {
+                empty += 1;
+                continue;
+            }
+
+            push_empty_run(&mut strip, empty);
+            empty = 0;
+            strip.push(DIGITS[column as usize] as char);
+        }
+        strips.push(strip);
+    }
+
+    strips.join("z")
+}
+
+fn push_empty_run(strip: &mut String, mut empty: usize) {
+    while empty >= 4 {
+        let run = empty.min(39);
+        strip.push('y');
+        strip.push(RUN_DIGITS[run - 4] as char);
+        empty -= run;
+    }
+
+    match empty {
+        1 => strip.push('0'),
+        2 => strip.push('w'),
+        3 => strip.push('x'),
+        _ => {}
+    }
+}
+
+/// Decodes a pattern in extended Wechsler format
+pub fn decode_wechsler(code: &str) -> Result<Pattern, GOLError> {
+    let invalid = || GOLError::InvalidPattern(code.to_string());
+    let mut points = Vec::new();
+    let (mut x, mut top) = (0, 0);
+
+    let mut chars = code.bytes();
+    while let Some(c) = chars.next() {
+        match c {
+            b'z' => {
+                x = 0;
+                top += 5;
+            }
+            b'w' => x += 2,
+            b'x' => x += 3,
+            b'y' => {
+                let run = chars.next().ok_or_else(invalid)?;
+                x += 4 + RUN_DIGITS
+                    .iter()
+                    .position(|d| *d == run)
+                    .ok_or_else(invalid)?;
+            }
+            _ => {
+                let column = DIGITS.iter().position(|d| *d == c).ok_or_else(invalid)?;
+                for row in 0..5 {
+                    if column & 1 << row != 0 {
+                        points.push((x, top + row));
+                    }
+                }
+                x += 1;
+            }
+        }
+    }
+
+    if points.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(Pattern::from_points(&points))
+}
+
+/// The prefix of an apgcode: `xs` and the population for still lifes, `xp` and the period for
+/// oscillators and `xq` and the period for spaceships
+pub fn prefix(kind: Kind, population: usize) -> Option<String> {
+    match kind {
+        Kind::StillLife => Some(format!("xs{}", population)),
+        Kind::Oscillator { period } => Some(format!("xp{}", period)),
+        Kind::Spaceship { period } => Some(format!("xq{}", period)),
+        Kind::Unknown => None,
+    }
+}
+
+/// The apgcode of a pattern, if it is a still life, oscillator or spaceship
+pub fn encode(pattern: &Pattern) -> Option<String> {
+    Object::classify(pattern, GOLMethod::Normal, &Default::default()).apgcode()
+}
+
+/// Decodes an apgcode back into a pattern
+pub fn decode(code: &str) -> Result<Pattern, GOLError> {
+    let invalid = || GOLError::InvalidPattern(code.to_string());
+    let (prefix, cells) = code.split_once('_').ok_or_else(invalid)?;
+
+    let number = prefix
+        .strip_prefix("xs")
+        .or_else(|| prefix.strip_prefix("xp"))
+        .or_else(|| prefix.strip_prefix("xq"))
+        .ok_or_else(invalid)?;
+    number.parse::<u64>().map_err(|_| invalid())?;
+
+    decode_wechsler(cells).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, wechsler};
+    use crate::pattern::Pattern;
+
+    #[test]
+    pub fn test_encode() {
+        assert_eq!(
+            Some("xs4_33".to_string()),
+            encode(&Pattern::from_rows(&["OO", "OO"]))
+        );
+        assert_eq!(
+            Some("xp2_7".to_string()),
+            encode(&Pattern::from_rows(&["OOO"]))
+        );
+        assert_eq!(
+            Some("xq4_153".to_string()),
+            encode(&Pattern::from_rows(&[".O.", "..O", "OOO"]))
+        );
+        assert_eq!(
+            Some("xp15_4r4z4r4".to_string()),
+            encode(&Pattern::from_rows(&[
+                "..O....O..",
+                "OO.OOOO.OO",
+                "..O....O.."
+            ]))
+        );
+    }
+
+    #[test]
+    pub fn test_decode() {
+        let pattern = decode("xp15_4r4z4r4").unwrap();
+
+        assert_eq!(3, pattern.width);
+        assert_eq!(10, pattern.height);
+        assert_eq!(12, pattern.population());
+        assert_eq!("4r4z4r4", wechsler(&pattern));
+
+        let sparse = Pattern::from_points(&[(0, 0), (45, 7)]);
+        assert_eq!(
+            sparse,
+            decode(&format!("xs2_{}", wechsler(&sparse))).unwrap()
+        );
+
+        assert!(decode("xs4_3!").is_err());
+        assert!(decode("block").is_err());
+    }
+}
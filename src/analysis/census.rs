@@ -0,0 +1,387 @@
+use std::{collections::HashMap, fmt, sync::OnceLock};
+
+use crate::{
+    args::{Connectivity, GOLMethod},
+    boards::{gol_board::Board, hensel},
+    pattern::Pattern,
+};
+
+use super::{apgcode, period::CycleDetector};
+
+/// Empty cells left around an object when it is run on its own
+const MARGIN: usize = 16;
+/// Generations an object is run for on its own before giving up on classifying it
+const MAX_GENERATIONS: u64 = 64;
+
+/// Common objects, in one of their phases
+const KNOWN_OBJECTS: &[(&str, &[&str])] = &[
+    ("block", &["OO", "OO"]),
+    ("beehive", &[".OO.", "O..O", ".OO."]),
+    ("loaf", &[".OO.", "O..O", ".O.O", "..O."]),
+    ("boat", &["OO.", "O.O", ".O."]),
+    ("tub", &[".O.", "O.O", ".O."]),
+    ("ship", &["OO.", "O.O", ".OO"]),
+    ("pond", &[".OO.", "O..O", "O..O", ".OO."]),
+    ("long boat", &["OO..", "O.O.", ".O.O", "..O."]),
+    ("barge", &[".O..", "O.O.", ".O.O", "..O."]),
+    ("blinker", &["OOO"]),
+    ("toad", &[".OOO", "OOO."]),
+    ("beacon", &["OO..", "OO..", "..OO", "..OO"]),
+    (
+        "pulsar",
+        &[
+            "..OOO...OOO..",
+            ".............",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            "..OOO...OOO..",
+            ".............",
+            "..OOO...OOO..",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            ".............",
+            "..OOO...OOO..",
+        ],
+    ),
+    (
+        "pentadecathlon",
+        &["..O....O..", "OO.OOOO.OO", "..O....O.."],
+    ),
+    ("glider", &[".O.", "..O", "OOO"]),
+    (
+        "lightweight spaceship",
+        &[".O..O", "O....", "O...O", "OOOO."],
+    ),
+    (
+        "middleweight spaceship",
+        &["...O..", ".O...O", "O.....", "O....O", "OOOOO."],
+    ),
+    (
+        "heavyweight spaceship",
+        &["...OO..", ".O....O", "O......", "O.....O", "OOOOOO."],
+    ),
+];
+
+/// The pattern of a common object, such as `glider` or `long boat`
+pub fn known_object(name: &str) -> Option<Pattern> {
+    KNOWN_OBJECTS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, rows)| Pattern::from_rows(rows))
+}
+
+/// The common objects as classified under B3/S23, worked out the first time they are needed
+fn known_objects() -> &'static [(&'static str, Object)] {
+    static OBJECTS: OnceLock<Vec<(&'static str, Object)>> = OnceLock::new();
+    OBJECTS.get_or_init(|| {
+        KNOWN_OBJECTS
+            .iter()
+            .map(|(name, rows)| {
+                let object = Object::classify(
+                    &Pattern::from_rows(rows),
+                    GOLMethod::Normal,
+                    &Default::default(),
+                );
+                (*name, object)
+            })
+            .collect()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CensusOptions {
+    /// Which cells count as touching
+    pub connectivity: Connectivity,
+    /// Live cells this close together belong to the same object
+    pub distance: usize,
+}
+
+impl Default for CensusOptions {
+    fn default() -> CensusOptions {
+        CensusOptions {
+            connectivity: Connectivity::Moore,
+            distance: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    StillLife,
+    Oscillator {
+        period: u64,
+    },
+    Spaceship {
+        period: u64,
+    },
+    /// The object changes or dies when run on its own
+    Unknown,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::StillLife => write!(f, "still life"),
+            Kind::Oscillator { period } => write!(f, "period {} oscillator", period),
+            Kind::Spaceship { period } => write!(f, "period {} spaceship", period),
+            Kind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// An object found on the board, in the same canonical phase and orientation wherever it was
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Object {
+    pub pattern: Pattern,
+    pub kind: Kind,
+}
+
+impl Object {
+    /// Runs a pattern on its own with the given rule to find out what kind of object it is, then
+    /// picks the smallest of its phases and orientations. `rule` is only used by
+    /// [`GOLMethod::Isotropic`].
+    pub fn classify(pattern: &Pattern, method: GOLMethod, rule: &hensel::Rule) -> Object {
+        let mut board = pattern.to_board(MARGIN, method);
+        board.rule = rule.clone();
+        let mut detector = CycleDetector::new();
+        let mut phases = Vec::new();
+
+        for generation in 0..MAX_GENERATIONS {
+            if let Some(cycle) = detector.observe(generation, &board) {
+                let kind = if cycle.start != 0 || board.population() == 0 {
+                    Kind::Unknown
+                } else if cycle.is_spaceship() {
+                    Kind::Spaceship {
+                        period: cycle.period,
+                    }
+                } else if cycle.period == 1 {
+                    Kind::StillLife
+                } else {
+                    Kind::Oscillator {
+                        period: cycle.period,
+                    }
+                };
+
+                if kind == Kind::Unknown {
+                    break;
+                }
+
+                return Object {
+                    pattern: canonical(&phases),
+                    kind,
+                };
+            }
+
+            phases.push(Pattern::from_board(&board));
+            board.step();
+        }
+
+        Object {
+            pattern: canonical(std::slice::from_ref(pattern)),
+            kind: Kind::Unknown,
+        }
+    }
+
+    /// The apgcode of the object, unless its kind is unknown
+    pub fn apgcode(&self) -> Option<String> {
+        let prefix = apgcode::prefix(self.kind, self.pattern.population())?;
+
+        Some(format!("{}_{}", prefix, apgcode::wechsler(&self.pattern)))
+    }
+
+    /// The common name of the object, if it has one and behaves as it does under B3/S23
+    pub fn name(&self) -> Option<&'static str> {
+        known_objects()
+            .iter()
+            .find(|(_, object)| object == self)
+            .map(|(name, _)| *name)
+    }
+}
+
+/// The phase and orientation with the shortest extended Wechsler code, then the first in
+/// alphabetical order, as apgcodes are chosen
+fn canonical(phases: &[Pattern]) -> Pattern {
+    phases
+        .iter()
+        .flat_map(|phase| phase.orientations())
+        .map(|pattern| (apgcode::wechsler(&pattern), pattern))
+        .min_by(|(a, _), (b, _)| (a.len(), a).cmp(&(b.len(), b)))
+        .map(|(_, pattern)| pattern)
+        .unwrap()
+}
+
+/// Splits the live cells of a board into groups of touching cells
+pub fn components(board: &Board, options: CensusOptions) -> Vec<Vec<(usize, usize)>> {
+    let distance = options.distance as isize;
+    let mut seen = vec![vec![false; board.width]; board.height];
+    let mut components = Vec::new();
+
+    for y in 0..board.height {
+        for x in 0..board.width {
+            if !board.get(x, y) || seen[y][x] {
+                continue;
+            }
+
+            seen[y][x] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![(x, y)];
+            while let Some((cx, cy)) = stack.pop() {
+                component.push((cx, cy));
+                for dy in -distance..=distance {
+                    for dx in -distance..=distance {
+                        if options.connectivity == Connectivity::Orthogonal
+                            && dx.abs() + dy.abs() > distance
+                        {
+                            continue;
+                        }
+
+                        let nx = cx as isize + dx;
+                        let ny = cy as isize + dy;
+                        if nx < 0
+                            || ny < 0
+                            || nx as usize >= board.width
+                            || ny as usize >= board.height
+                        {
+                            continue;
+                        }
+
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        if board.get(nx, ny) && !seen[ny][nx] {
+                            seen[ny][nx] = true;
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+    }
+
+    components
+}
+
+/// How many of each object a board is made of
+#[derive(Debug, Default)]
+pub struct Census {
+    pub objects: Vec<(Object, usize)>,
+}
+
+impl Census {
+    /// Classifies the objects of a board under the board's own rule
+    pub fn take(board: &Board, options: CensusOptions) -> Census {
+        let mut counts: HashMap<Object, usize> = HashMap::new();
+        for component in components(board, options) {
+            let pattern = Pattern::from_points(&component);
+            let object = Object::classify(&pattern, board.method, &board.rule);
+            *counts.entry(object).or_default() += 1;
+        }
+
+        let mut objects: Vec<(Object, usize)> = counts.into_iter().collect();
+        objects.sort_by(|(a, a_count), (b, b_count)| {
+            b_count
+                .cmp(a_count)
+                .then(a.kind.cmp(&b.kind))
+                .then(a.pattern.cmp(&b.pattern))
+        });
+
+        Census { objects }
+    }
+
+    /// The number of objects with the given common name
+    pub fn count(&self, name: &str) -> usize {
+        self.objects
+            .iter()
+            .filter(|(object, _)| object.name() == Some(name))
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (object, count) in &self.objects {
+            match (object.apgcode(), object.name()) {
+                (Some(code), Some(name)) => writeln!(f, "{} {} ({})", count, code, name)?,
+                (Some(code), None) => writeln!(f, "{} {}", count, code)?,
+                (None, _) => writeln!(
+                    f,
+                    "{} unknown {}x{} with {} live cells",
+                    count,
+                    object.pattern.width,
+                    object.pattern.height,
+                    object.pattern.population()
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Census, CensusOptions, Kind, Object};
+    use crate::{args::GOLMethod, boards::gol_board::Board, pattern::Pattern};
+
+    fn stamp(board: &mut Board, rows: &[&str], left: usize, top: usize) {
+        let pattern = Pattern::from_rows(rows);
+        for y in 0..pattern.height {
+            for x in 0..pattern.width {
+                if pattern.get(x, y) {
+                    board.set(left + x, top + y, true);
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_classify() {
+        let life = |rows: &[&str]| {
+            Object::classify(
+                &Pattern::from_rows(rows),
+                GOLMethod::Normal,
+                &Default::default(),
+            )
+        };
+
+        let glider = life(&["OOO", "O..", ".O."]);
+        assert_eq!(Kind::Spaceship { period: 4 }, glider.kind);
+        assert_eq!(Some("glider"), glider.name());
+
+        let blinker = life(&["O", "O", "O"]);
+        assert_eq!(Kind::Oscillator { period: 2 }, blinker.kind);
+        assert_eq!(Some("blinker"), blinker.name());
+
+        // The same cells under other rules
+        let glider = Pattern::from_rows(&["OOO", "O..", ".O."]);
+        let rule = "B3/S23".parse().unwrap();
+        let isotropic = Object::classify(&glider, GOLMethod::Isotropic, &rule);
+        assert_eq!(Some("glider"), isotropic.name());
+        let rule = "B2/S23".parse().unwrap();
+        assert_eq!(
+            None,
+            Object::classify(&glider, GOLMethod::Isotropic, &rule).name()
+        );
+        assert_eq!(
+            None,
+            Object::classify(&glider, GOLMethod::VonNeumann, &Default::default()).name()
+        );
+    }
+
+    #[test]
+    pub fn test_census() {
+        let mut board = Board::dead_state(30, 12, GOLMethod::Normal);
+        stamp(&mut board, &["OO", "OO"], 1, 1);
+        stamp(&mut board, &["OO", "OO"], 10, 1);
+        stamp(&mut board, &["OOO"], 1, 8);
+        stamp(&mut board, &[".O.", "O.O", ".OO"], 20, 5);
+
+        let census = Census::take(&board, CensusOptions::default());
+
+        assert_eq!(2, census.count("block"));
+        assert_eq!(1, census.count("blinker"));
+        assert_eq!(1, census.count("boat"));
+    }
+}
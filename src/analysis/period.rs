@@ -0,0 +1,217 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    fmt,
+};
+
+use crate::{boards::gol_board::Board, pattern::Pattern};
+
+/// A repeating sequence of generations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub period: u64,
+    /// The first generation of the cycle
+    pub start: u64,
+    /// How far the pattern moves each period, `(0, 0)` unless it is a spaceship
+    pub displacement: (isize, isize),
+}
+
+impl Cycle {
+    pub fn is_spaceship(&self) -> bool {
+        self.displacement != (0, 0)
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_spaceship() {
+            write!(
+                f,
+                "period {} moving ({}, {}) since gen {}",
+                self.period, self.displacement.0, self.displacement.1, self.start
+            )
+        } else {
+            write!(f, "period {} since gen {}", self.period, self.start)
+        }
+    }
+}
+
+/// The most generations a [`CycleDetector`] remembers, so longer cycles aren't found
+const HISTORY: usize = 4096;
+/// The most cells, counted over the bounding boxes of the generations remembered, a
+/// [`CycleDetector`] keeps before it forgets the oldest generations
+const HISTORY_CELLS: usize = 1 << 24;
+
+/// A generation remembered by a [`CycleDetector`]
+#[derive(Debug)]
+struct Seen {
+    generation: u64,
+    /// The top left corner of the bounding box
+    origin: (usize, usize),
+    pattern: Pattern,
+}
+
+/// Watches successive generations of a board for a repeated pattern, relative to its bounding box
+/// so that patterns that repeat in a different place are found as well as still lifes and
+/// oscillators. Generations are looked up by hash and compared cell by cell before a match is
+/// taken, and only the most recent ones are remembered.
+#[derive(Debug, Default)]
+pub struct CycleDetector {
+    seen: HashMap<u64, Seen>,
+    /// The hashes and generations remembered, oldest first
+    order: VecDeque<(u64, u64)>,
+    cells: usize,
+    cycle: Option<Cycle>,
+}
+
+impl CycleDetector {
+    pub fn new() -> CycleDetector {
+        CycleDetector::default()
+    }
+
+    /// The cycle found so far, if any
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// Records a generation of the board, returning the cycle once one is found
+    pub fn observe(&mut self, generation: u64, board: &Board) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return self.cycle;
+        }
+
+        let (hash, origin) = board.pattern_hash();
+        let pattern = Pattern::from_board(board);
+        if let Some(seen) = self.seen.get(&hash).filter(|seen| seen.pattern == pattern) {
+            self.cycle = Some(Cycle {
+                period: generation - seen.generation,
+                start: seen.generation,
+                displacement: (
+                    origin.0 as isize - seen.origin.0 as isize,
+                    origin.1 as isize - seen.origin.1 as isize,
+                ),
+            });
+            return self.cycle;
+        }
+
+        self.cells += pattern.width * pattern.height;
+        let seen = Seen {
+            generation,
+            origin,
+            pattern,
+        };
+        // A different pattern with the same hash is replaced by the newer one
+        if let Some(replaced) = self.seen.insert(hash, seen) {
+            self.cells -= replaced.pattern.width * replaced.pattern.height;
+        }
+        self.order.push_back((hash, generation));
+
+        while self.order.len() > HISTORY || self.cells > HISTORY_CELLS {
+            let Some((oldest, oldest_generation)) = self.order.pop_front() else {
+                break;
+            };
+            // Generations that were replaced have already been forgotten
+            if let Entry::Occupied(entry) = self.seen.entry(oldest) {
+                if entry.get().generation == oldest_generation {
+                    let forgotten = entry.remove();
+                    self.cells -= forgotten.pattern.width * forgotten.pattern.height;
+                }
+            }
+        }
+
+        self.cycle
+    }
+
+    /// Forgets every generation seen, for when the board is edited
+    pub fn reset(&mut self) {
+        *self = CycleDetector::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CycleDetector;
+    use crate::{args::GOLMethod, boards::gol_board::Board, pattern::Pattern};
+
+    fn run(mut board: Board, generations: u64) -> Option<super::Cycle> {
+        let mut detector = CycleDetector::new();
+        for generation in 0..generations {
+            if let Some(cycle) = detector.observe(generation, &board) {
+                return Some(cycle);
+            }
+            board = board.next_state();
+        }
+
+        None
+    }
+
+    #[test]
+    pub fn test_blinker() {
+        let mut board = Board::dead_state(5, 5, GOLMethod::Normal);
+        for x in 1..4 {
+            board.set(x, 2, true);
+        }
+
+        let cycle = run(board, 10).unwrap();
+
+        assert_eq!(2, cycle.period);
+        assert_eq!(0, cycle.start);
+        assert!(!cycle.is_spaceship());
+    }
+
+    #[test]
+    pub fn test_glider() {
+        let mut board = Board::dead_state(20, 20, GOLMethod::Normal);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            board.set(x, y, true);
+        }
+
+        let cycle = run(board, 10).unwrap();
+
+        assert_eq!(4, cycle.period);
+        assert_eq!((1, 1), cycle.displacement);
+    }
+
+    #[test]
+    pub fn test_hash_matches_are_compared() {
+        let mut board = Board::dead_state(5, 5, GOLMethod::Normal);
+        board.set(2, 2, true);
+        board.set(3, 2, true);
+
+        // Something else stored under the same hash, as if the hashes collided
+        let mut detector = CycleDetector::new();
+        let (hash, origin) = board.pattern_hash();
+        detector.seen.insert(
+            hash,
+            super::Seen {
+                generation: 0,
+                origin,
+                pattern: Pattern::from_rows(&["O", "O"]),
+            },
+        );
+        detector.order.push_back((hash, 0));
+
+        assert_eq!(None, detector.observe(1, &board));
+        assert_eq!(1, detector.observe(2, &board).unwrap().period);
+    }
+
+    #[test]
+    pub fn test_history_is_bounded() {
+        // Two cells further apart every generation never repeat
+        let pair = |distance: usize| {
+            let mut board = Board::dead_state(110, 50, GOLMethod::Normal);
+            board.set(0, 0, true);
+            board.set(distance % 100 + 1, distance / 100, true);
+            board
+        };
+
+        let mut detector = CycleDetector::new();
+        for distance in 0..super::HISTORY + 10 {
+            assert_eq!(None, detector.observe(distance as u64, &pair(distance)));
+        }
+        assert_eq!(super::HISTORY, detector.seen.len());
+
+        let generation = super::HISTORY as u64 + 10;
+        assert_eq!(None, detector.observe(generation, &pair(0)));
+        assert!(detector.observe(generation + 1, &pair(20)).is_some());
+    }
+}
@@ -0,0 +1,240 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+    args::GOLMethod,
+    boards::gol_board::Board,
+    pattern::Pattern,
+    soup::{Region, Soup},
+};
+
+use super::{
+    census::{self, Census, CensusOptions, Kind, Object},
+    period::CycleDetector,
+};
+
+/// apgcodes of the objects nearly every soup leaves behind: block, blinker, beehive, loaf, boat,
+/// tub, ship, pond and glider
+const COMMON_OBJECTS: &[&str] = &[
+    "xs4_33", "xp2_7", "xs6_696", "xs7_2596", "xs5_253", "xs4_252", "xs6_356", "xs8_6996",
+    "xq4_153",
+];
+/// Objects with live cells this close to the edge of the board are checked for being spaceships
+/// on their way out
+const EDGE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchOptions {
+    /// Width and height of the random region
+    pub size: usize,
+    /// The chance of each cell in the region being alive
+    pub density: f64,
+    /// Empty cells around the region, so that the soup has room to grow
+    pub padding: usize,
+    /// Generations a soup is run for before giving up on it settling
+    pub generations: u64,
+}
+
+/// What became of one soup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoupResult {
+    pub seed: u64,
+    /// The generation the soup settled at, `None` if it was still changing when given up on
+    pub lifespan: Option<u64>,
+    pub population: usize,
+    /// apgcodes of the objects left behind or flown off that aren't among the common ones
+    pub rare: Vec<String>,
+}
+
+/// The soup for a seed: a square of random cells in the middle of an empty board
+pub fn soup(seed: u64, options: &SearchOptions) -> Board {
+    let region = Region {
+        width: options.size,
+        height: options.size,
+        position: None,
+    };
+    let soup = Soup::new(Some(seed), options.density, Some(region));
+
+    let side = options.size + 2 * options.padding;
+    let mut board = Board::dead_state(side, side, GOLMethod::Normal);
+    for (x, y) in soup.cells(side, side) {
+        board.set(x, y, true);
+    }
+
+    board
+}
+
+/// Runs the soup for a seed until it settles and takes a census of what is left
+pub fn run_soup(seed: u64, options: &SearchOptions) -> SoupResult {
+    settle(seed, soup(seed, options), options)
+}
+
+/// Runs a board until it settles, taking spaceships off it before they crash into its edges
+fn settle(seed: u64, mut board: Board, options: &SearchOptions) -> SoupResult {
+    let mut detector = CycleDetector::new();
+    let mut lifespan = None;
+    let mut edge = EdgeWatch::default();
+
+    for generation in 0..=options.generations {
+        edge.remove_escaping(&mut board);
+        if let Some(cycle) = detector.observe(generation, &board) {
+            lifespan = Some(cycle.start);
+            break;
+        }
+        board = board.next_state();
+    }
+
+    let mut rare: Vec<String> = match lifespan {
+        Some(_) => Census::take(&board, CensusOptions::default())
+            .objects
+            .iter()
+            .filter_map(|(object, _)| object.apgcode())
+            .chain(edge.escaped)
+            .filter(|code| !COMMON_OBJECTS.contains(&code.as_str()))
+            .collect(),
+        None => Vec::new(),
+    };
+    rare.sort();
+    rare.dedup();
+
+    SoupResult {
+        seed,
+        lifespan,
+        population: board.population(),
+        rare,
+    }
+}
+
+/// Takes spaceships off a board before they crash into its edges and turn into debris
+#[derive(Debug, Default)]
+struct EdgeWatch {
+    /// Where each shape was last seen near the edge. A shape seen again somewhere else is
+    /// likely to be a spaceship, and only those are run on their own, as that is slow.
+    seen: HashMap<Pattern, (usize, usize)>,
+    classified: HashMap<Pattern, Object>,
+    /// apgcodes of the spaceships taken off the board
+    escaped: Vec<String>,
+}
+
+impl EdgeWatch {
+    fn remove_escaping(&mut self, board: &mut Board) {
+        let (width, height) = (board.width, board.height);
+        let near_edge = |(x, y): &(usize, usize)| {
+            *x < EDGE || *y < EDGE || *x + EDGE >= width || *y + EDGE >= height
+        };
+
+        let edge_alive = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(near_edge)
+            .any(|(x, y)| board.get(x, y));
+        if !edge_alive {
+            return;
+        }
+
+        for component in census::components(board, CensusOptions::default()) {
+            if !component.iter().any(near_edge) {
+                continue;
+            }
+
+            let left = component.iter().map(|(x, _)| *x).min().unwrap();
+            let top = component.iter().map(|(_, y)| *y).min().unwrap();
+            let pattern = Pattern::from_points(&component);
+            match self.seen.insert(pattern.clone(), (left, top)) {
+                Some(position) if position != (left, top) => {}
+                _ => continue,
+            }
+
+            let object = self
+                .classified
+                .entry(pattern)
+                .or_insert_with_key(|pattern| Object::classify(pattern, board.method, &board.rule));
+            if let Kind::Spaceship { .. } = object.kind {
+                for (x, y) in component {
+                    board.set(x, y, false);
+                }
+                self.escaped.extend(object.apgcode());
+            }
+        }
+    }
+}
+
+/// Runs `count` soups with consecutive seeds starting at `first_seed`, the longest lived first.
+/// Soups that never settled come before all the others.
+pub fn search(first_seed: u64, count: u64, options: &SearchOptions) -> Vec<SoupResult> {
+    let mut results: Vec<SoupResult> = (0..count)
+        .map(|i| run_soup(first_seed.wrapping_add(i), options))
+        .collect();
+
+    results.sort_by_key(|result| (Reverse(result.lifespan.unwrap_or(u64::MAX)), result.seed));
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_soup, settle, soup, SearchOptions, COMMON_OBJECTS};
+    use crate::{
+        analysis::{apgcode, census::Object},
+        args::GOLMethod,
+        boards::gol_board::Board,
+        pattern::Pattern,
+    };
+
+    const OPTIONS: SearchOptions = SearchOptions {
+        size: 8,
+        density: 0.5,
+        padding: 12,
+        generations: 2000,
+    };
+
+    #[test]
+    pub fn test_common_objects() {
+        for code in COMMON_OBJECTS {
+            let object = Object::classify(
+                &apgcode::decode(code).unwrap(),
+                GOLMethod::Normal,
+                &Default::default(),
+            );
+
+            assert_eq!(Some(code.to_string()), object.apgcode());
+            assert!(object.name().is_some());
+        }
+    }
+
+    #[test]
+    pub fn test_reproducible() {
+        assert_eq!(
+            soup(7, &OPTIONS).state_hash(),
+            soup(7, &OPTIONS).state_hash()
+        );
+        assert_ne!(
+            soup(7, &OPTIONS).state_hash(),
+            soup(8, &OPTIONS).state_hash()
+        );
+        assert_eq!(run_soup(7, &OPTIONS), run_soup(7, &OPTIONS));
+    }
+
+    #[test]
+    pub fn test_escaping_spaceships() {
+        // A lightweight spaceship heading left and a glider heading for the bottom right corner,
+        // leaving a block behind
+        let block = Pattern::from_rows(&["OO", "OO"]);
+        let lwss = Pattern::from_rows(&[".O..O", "O....", "O...O", "OOOO."]);
+        let glider = Pattern::from_rows(&[".O.", "..O", "OOO"]);
+        let mut board = Board::dead_state(40, 30, GOLMethod::Normal);
+        for (pattern, left, top) in [(&block, 20, 10), (&lwss, 8, 4), (&glider, 25, 18)] {
+            for y in 0..pattern.height {
+                for x in 0..pattern.width {
+                    if pattern.get(x, y) {
+                        board.set(left + x, top + y, true);
+                    }
+                }
+            }
+        }
+
+        let result = settle(0, board, &OPTIONS);
+
+        assert!(result.lifespan.is_some());
+        assert_eq!(4, result.population);
+        assert_eq!(vec![apgcode::encode(&lwss).unwrap()], result.rare);
+    }
+}
@@ -2,8 +2,11 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand, ValueEnum};
 
+use crate::{export::Color, scene::Placement, soup::Region};
+
 const DEFAULT_WIDTH: usize = 105;
 const DEFAULT_HEIGHT: usize = 23;
+const DEFAULT_FPS: u32 = 30;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about=None)]
@@ -20,6 +23,26 @@ pub enum GOLMethod {
     VonNeumann,
     /// Day and Night: B3678/S34678
     Dan,
+    /// An isotropic non-totalistic rule in Hensel notation, given with --rule
+    Isotropic,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Grid {
+    /// Square cells, the rule is chosen with --method or --rule
+    Square,
+    /// Hexagonal cells with six neighbors, the default rule is B2/S34H
+    Hex,
+    /// Triangular cells with edge and vertex neighbors, the default rule is B4/S345L
+    Tri,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Connectivity {
+    /// Cells within --distance steps horizontally plus vertically touch
+    Orthogonal,
+    /// Cells within --distance steps in any direction, including diagonally, touch
+    Moore,
 }
 
 #[derive(Subcommand, Debug)]
@@ -30,39 +53,118 @@ pub enum SubCommands {
     File(File),
     /// Run Langton's Ant
     Ant(Ant),
+    /// Load a Wireworld circuit from a file
+    Wire(Wire),
+    /// Create a grid randomly populated and run a Larger than Life rule on it
+    Ltl(Ltl),
+    /// Create a grid randomly populated and run a Margolus block rule on it
+    Margolus(Margolus),
+    /// Run a pattern from a file without the UI and print the final board
+    Run(Run),
+    /// Record a pattern from a file, or Langton's Ant, as an animated GIF
+    Record(Record),
+    /// Run many random soups and report the longest lived and the rare objects they leave
+    Search(Search),
+}
+
+/// How a board is filled at random
+#[derive(clap::Args, Debug)]
+pub struct SoupArgs {
+    /// The seed for the random cells, shown in the UI so that the board can be made again. Random
+    /// if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// The chance of each random cell being alive, from 0 to 1
+    #[arg(long, default_value_t = 0.5, value_parser = parse_density)]
+    pub density: f64,
+
+    /// Only fill a part of the board: WxH in the center, or WxH+X+Y with its top left cell at X,Y
+    #[arg(long)]
+    pub region: Option<Region>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct Random {
     /// Width of the grid
-    #[arg(default_value_t = DEFAULT_WIDTH)]
+    #[arg(default_value_t = DEFAULT_WIDTH, value_parser = parse_size)]
     pub width: usize,
 
     /// Height of the grid
-    #[arg(default_value_t = DEFAULT_HEIGHT)]
+    #[arg(default_value_t = DEFAULT_HEIGHT, value_parser = parse_size)]
     pub height: usize,
 
     /// The method used for the simulation
     #[arg(long, short, value_enum, default_value_t = GOLMethod::Normal)]
     pub method: GOLMethod,
 
-    /// The time slept between updating the board (in milliseconds)
+    /// A rule in Hensel notation such as B2-a/S12, or tlife (implies the isotropic method). On
+    /// hexagonal and triangular grids a rule such as B2/S34H or B4/S345L (LE: edge neighbors only,
+    /// LV: vertex neighbors only), with commas between the counts if any is above 9: B4/S3,4,10L
+    #[arg(long, short)]
+    pub rule: Option<String>,
+
+    /// The shape of the cells
+    #[arg(long, short, value_enum, default_value_t = Grid::Square)]
+    pub grid: Grid,
+
+    #[command(flatten)]
+    pub soup: SoupArgs,
+
+    #[command(flatten)]
+    pub place: PlaceArgs,
+
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
     #[arg(long, short, default_value_t = 200)]
     pub sleep_time: u64,
+
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub start: StartArgs,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct File {
-    /// File to load from
+    /// File to load from, a PNG or PBM image, or an apgcode such as xp15_4r4z4r4
     pub filepath: PathBuf,
 
     /// The method used for the simulation
     #[arg(long, short, value_enum, default_value_t = GOLMethod::Normal)]
     pub method: GOLMethod,
 
-    /// The time slept between updating the board (in milliseconds)
+    /// A rule in Hensel notation such as B2-a/S12, or tlife (implies the isotropic method). On
+    /// hexagonal and triangular grids a rule such as B2/S34H or B4/S345L (LE: edge neighbors only,
+    /// LV: vertex neighbors only), with commas between the counts if any is above 9: B4/S3,4,10L
+    #[arg(long, short)]
+    pub rule: Option<String>,
+
+    /// The shape of the cells
+    #[arg(long, short, value_enum, default_value_t = Grid::Square)]
+    pub grid: Grid,
+
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
     #[arg(long, short, default_value_t = 200)]
     pub sleep_time: u64,
+
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub start: StartArgs,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+
+    #[command(flatten)]
+    pub import: ImportArgs,
+
+    #[command(flatten)]
+    pub place: PlaceArgs,
 }
 
 // Add argument for Langtons' ant
@@ -70,18 +172,377 @@ pub struct File {
 #[derive(clap::Args, Debug)]
 pub struct Ant {
     /// Width of the grid
-    #[arg(default_value_t = DEFAULT_WIDTH)]
+    #[arg(default_value_t = DEFAULT_WIDTH, value_parser = parse_size)]
     pub width: usize,
 
     /// Height of the grid
-    #[arg(default_value_t = DEFAULT_HEIGHT)]
+    #[arg(default_value_t = DEFAULT_HEIGHT, value_parser = parse_size)]
     pub height: usize,
 
-    /// The time slept between updating the board (in milliseconds)
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
     #[arg(long, short, default_value_t = 200)]
     pub interval: u64,
 
-    /// The step to start at
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    /// Run to this step before showing the board, with a progress bar that Esc cancels
     #[arg(long, short, default_value_t = 0)]
     pub start: u64,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Wire {
+    /// Circuit file to load from ('#' conductor, '@' electron head, '~' electron tail, '.' empty)
+    pub filepath: PathBuf,
+
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
+    #[arg(long, short, default_value_t = 200)]
+    pub sleep_time: u64,
+
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub start: StartArgs,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Ltl {
+    /// The rule, either a rulestring such as R5,C0,M1,S34..58,B34..45,NM or one of bosco, majority
+    #[arg(default_value = "bosco")]
+    pub rule: String,
+
+    /// Width of the grid
+    #[arg(default_value_t = DEFAULT_WIDTH, value_parser = parse_size)]
+    pub width: usize,
+
+    /// Height of the grid
+    #[arg(default_value_t = DEFAULT_HEIGHT, value_parser = parse_size)]
+    pub height: usize,
+
+    #[command(flatten)]
+    pub soup: SoupArgs,
+
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
+    #[arg(long, short, default_value_t = 200)]
+    pub sleep_time: u64,
+
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub start: StartArgs,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum MargolusRule {
+    /// Critters: reversible, gliders appear from random soups
+    Critters,
+    /// Tron: reversible, inverts full and empty blocks
+    Tron,
+    /// The billiard ball machine: reversible, balls bounce off each other
+    Bbm,
+    /// Falling sand: grains fall and pile up, not reversible
+    Sand,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Margolus {
+    /// The block rule
+    #[arg(value_enum, default_value_t = MargolusRule::Critters)]
+    pub rule: MargolusRule,
+
+    /// Width of the grid
+    #[arg(default_value_t = DEFAULT_WIDTH, value_parser = parse_size)]
+    pub width: usize,
+
+    /// Height of the grid
+    #[arg(default_value_t = DEFAULT_HEIGHT, value_parser = parse_size)]
+    pub height: usize,
+
+    #[command(flatten)]
+    pub soup: SoupArgs,
+
+    /// The time between generations (in milliseconds), 0 to run as many as there is time for
+    #[arg(long, short, default_value_t = 200)]
+    pub sleep_time: u64,
+
+    #[command(flatten)]
+    pub draw: DrawArgs,
+
+    #[command(flatten)]
+    pub start: StartArgs,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Run {
+    /// File to load from, a PNG or PBM image, or an apgcode such as xp15_4r4z4r4
+    pub filepath: PathBuf,
+
+    /// The method used for the simulation
+    #[arg(long, short, value_enum, default_value_t = GOLMethod::Normal)]
+    pub method: GOLMethod,
+
+    /// A rule in Hensel notation such as B2-a/S12, or tlife (implies the isotropic method)
+    #[arg(long, short)]
+    pub rule: Option<String>,
+
+    /// The number of generations to run for
+    #[arg(long, short, default_value_t = 1000)]
+    pub generations: u64,
+
+    /// Stop as soon as the board settles into a still life, oscillator or spaceship
+    #[arg(long, short)]
+    pub until_stable: bool,
+
+    /// Split the final board into objects and print how many of each there are
+    #[arg(long)]
+    pub census: bool,
+
+    /// Which cells count as touching when splitting the board for the census
+    #[arg(long, value_enum, default_value_t = Connectivity::Moore)]
+    pub connectivity: Connectivity,
+
+    /// Live cells this close together belong to the same object in the census
+    #[arg(long, default_value_t = 1)]
+    pub distance: usize,
+
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+
+    /// Write the board to an image at each of these generations, e.g. 0,100,200
+    #[arg(long, value_delimiter = ',')]
+    pub snapshot_at: Vec<u64>,
+
+    /// The image the snapshots are named after, the generation is added to the name. PNG unless
+    /// it ends in .svg
+    #[arg(long, default_value = "snapshot.png")]
+    pub snapshot_out: PathBuf,
+
+    #[command(flatten)]
+    pub image: ImageArgs,
+
+    #[command(flatten)]
+    pub import: ImportArgs,
+
+    #[command(flatten)]
+    pub place: PlaceArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Search {
+    /// The number of soups to run
+    #[arg(default_value_t = 100)]
+    pub soups: u64,
+
+    /// The seed of the first soup, the others follow on from it. Random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Width and height of the random region of each soup
+    #[arg(long, default_value_t = 16)]
+    pub size: usize,
+
+    /// The chance of each cell in the random region being alive, from 0 to 1
+    #[arg(long, short, default_value_t = 0.5, value_parser = parse_density)]
+    pub density: f64,
+
+    /// Empty cells around the random region
+    #[arg(long, short, default_value_t = 32)]
+    pub padding: usize,
+
+    /// The number of generations a soup is run for before giving up on it settling
+    #[arg(long, short, default_value_t = 5000)]
+    pub generations: u64,
+
+    /// How many of the longest lived soups to report
+    #[arg(long, short, default_value_t = 10)]
+    pub top: usize,
+}
+
+fn parse_offset(offset: &str) -> Result<(usize, usize), String> {
+    offset
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or_else(|| format!("{} is not an offset such as 10,5", offset))
+}
+
+fn parse_placement(placement: &str) -> Result<Placement, String> {
+    placement
+        .parse()
+        .map_err(|_| format!("{} is not a placement such as glider@10,5:rot90", placement))
+}
+
+fn parse_size(size: &str) -> Result<usize, String> {
+    match size.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("{} is not a whole number above 0", size)),
+    }
+}
+
+fn parse_density(density: &str) -> Result<f64, String> {
+    match density.parse::<f64>() {
+        Ok(density) if (0.0..=1.0).contains(&density) => Ok(density),
+        _ => Err(format!("{} is not a number from 0 to 1", density)),
+    }
+}
+
+/// Where the numbers of each generation are written
+#[derive(clap::Args, Debug)]
+pub struct MetricsArgs {
+    /// Write the population, births, deaths and bounding box of every generation to this file,
+    /// as JSON Lines if it ends in .json or .jsonl and CSV otherwise
+    #[arg(id = "metrics", long = "metrics", value_name = "METRICS")]
+    pub path: Option<PathBuf>,
+}
+
+/// How the UI draws the board
+#[derive(clap::Args, Debug)]
+pub struct DrawArgs {
+    /// How many times a second the board is drawn, however fast generations run
+    #[arg(
+        long,
+        default_value_t = DEFAULT_FPS,
+        value_parser = clap::value_parser!(u32).range(1..=1000)
+    )]
+    pub fps: u32,
+}
+
+/// Where the UI starts
+#[derive(clap::Args, Debug)]
+pub struct StartArgs {
+    /// Run to this generation before showing the board, with a progress bar that Esc cancels
+    #[arg(
+        id = "start",
+        long = "start",
+        value_name = "START",
+        default_value_t = 0
+    )]
+    pub generation: u64,
+}
+
+/// How images are turned into boards, and where boards are placed
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Pixels of PNG and PBM images darker than this (0 to 255) are live cells
+    #[arg(long, default_value_t = 128)]
+    pub threshold: u8,
+
+    /// Make each cell from a square of this many pixels wide and high
+    #[arg(long, default_value_t = 1, value_parser = parse_size)]
+    pub scale: usize,
+
+    /// Make light pixels live cells instead of dark ones
+    #[arg(long)]
+    pub invert: bool,
+
+    /// Fill out lines of text boards shorter than the longest with dead cells instead of
+    /// rejecting the board
+    #[arg(long)]
+    pub pad: bool,
+
+    /// Place the pattern onto the board from this file instead of an empty one
+    #[arg(long)]
+    pub onto: Option<PathBuf>,
+
+    /// Where the top left corner of the pattern goes on the board given with --onto, as X,Y
+    #[arg(long, default_value = "0,0", value_parser = parse_offset, requires = "onto")]
+    pub offset: (usize, usize),
+}
+
+/// Patterns placed on the board before it starts
+#[derive(clap::Args, Debug, Default)]
+pub struct PlaceArgs {
+    /// Place a pattern, given as NAME@X,Y with any of :rot90, :rot180, :rot270, :flipx, :flipy and
+    /// :genN after it. NAME is a pattern from the library such as glider, an apgcode or a file.
+    /// Repeatable
+    #[arg(long, value_parser = parse_placement)]
+    pub place: Vec<Placement>,
+
+    /// Place every pattern listed in this file, one placement per line
+    #[arg(long)]
+    pub scene: Option<PathBuf>,
+}
+
+/// How boards are drawn as images
+#[derive(clap::Args, Debug)]
+pub struct ImageArgs {
+    /// Width and height of each cell in pixels
+    #[arg(long, short, default_value_t = 4, value_parser = parse_size)]
+    pub cell_size: usize,
+
+    /// The color of live cells
+    #[arg(long, default_value = "#ffffff")]
+    pub live_color: Color,
+
+    /// The color of dead cells
+    #[arg(long, default_value = "#000000")]
+    pub dead_color: Color,
+
+    /// The color of Langton's ant
+    #[arg(long, default_value = "#ff0000")]
+    pub ant_color: Color,
+
+    /// Draw lines of this color between the cells of SVG images
+    #[arg(long)]
+    pub grid_color: Option<Color>,
+
+    /// Only draw a part of the board: WxH in the center, or WxH+X+Y with its top left cell at X,Y
+    #[arg(long)]
+    pub crop: Option<Region>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Record {
+    /// File to load from, or an apgcode such as xp15_4r4z4r4
+    #[arg(required_unless_present = "ant")]
+    pub pattern: Option<PathBuf>,
+
+    /// Record Langton's Ant instead of a pattern
+    #[arg(long, conflicts_with = "pattern")]
+    pub ant: bool,
+
+    /// Width of the grid for Langton's Ant
+    #[arg(long, default_value_t = DEFAULT_WIDTH, value_parser = parse_size)]
+    pub width: usize,
+
+    /// Height of the grid for Langton's Ant
+    #[arg(long, default_value_t = DEFAULT_HEIGHT, value_parser = parse_size)]
+    pub height: usize,
+
+    /// The method used for the simulation
+    #[arg(long, short, value_enum, default_value_t = GOLMethod::Normal)]
+    pub method: GOLMethod,
+
+    /// A rule in Hensel notation such as B2-a/S12, or tlife (implies the isotropic method)
+    #[arg(long, short)]
+    pub rule: Option<String>,
+
+    /// The number of generations to record after the first
+    #[arg(long, short, default_value_t = 300)]
+    pub generations: u64,
+
+    /// The GIF file to write
+    #[arg(long, short, default_value = "run.gif")]
+    pub out: PathBuf,
+
+    /// The time between frames (in milliseconds)
+    #[arg(long, short, default_value_t = 100)]
+    pub delay: u64,
+
+    #[command(flatten)]
+    pub image: ImageArgs,
 }
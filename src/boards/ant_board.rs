@@ -8,8 +8,8 @@ pub struct Board {
     ant: (usize, usize, Direction),
 }
 
-#[derive(Clone)]
-enum Direction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -17,6 +17,15 @@ enum Direction {
 }
 
 impl Direction {
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+
     pub fn rotate_right(&mut self) {
         match self {
             Direction::Up => *self = Direction::Right,
@@ -50,6 +59,19 @@ impl Board {
         }
     }
 
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.state[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, black: bool) {
+        self.state[y][x] = black;
+    }
+
+    /// Where the ant is and which way it is facing
+    pub fn ant(&self) -> (usize, usize, Direction) {
+        self.ant
+    }
+
     pub fn update(&self) -> Board {
         let mut new_board = self.clone();
         let is_white = !self.state[self.ant.1][self.ant.0];
@@ -69,7 +91,7 @@ impl Board {
                 }
             }
             Direction::Down => {
-                if self.ant.1 < self.height - 1 {
+                if self.ant.1 + 1 < self.height {
                     new_board.ant.1 += 1
                 } else {
                     new_board.ant.1 = 0
@@ -83,7 +105,7 @@ impl Board {
                 }
             }
             Direction::Right => {
-                if self.ant.0 < self.width - 1 {
+                if self.ant.0 + 1 < self.width {
                     new_board.ant.0 += 1
                 } else {
                     new_board.ant.0 = 0
@@ -1,15 +1,24 @@
-use rand::prelude::*;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+};
 
-use crate::args::GOLMethod;
+use crate::{args::GOLMethod, soup::Soup, GOLError};
+
+use super::hensel;
 
 type StateArray = Vec<Vec<bool>>;
 
-#[derive(Debug)]
+/// A bounded grid of cells run with Conway's rule, a variation of it, or an isotropic
+/// non-totalistic rule. Cells beyond the edges are always dead.
+#[derive(Debug, Clone)]
 pub struct Board {
     state: StateArray,
     pub width: usize,
     pub height: usize,
     pub method: GOLMethod,
+    pub rule: hensel::Rule,
 }
 
 impl Board {
@@ -21,19 +30,128 @@ impl Board {
             width,
             height,
             method,
+            rule: hensel::Rule::default(),
         }
     }
 
-    pub fn random_state(width: usize, height: usize, method: GOLMethod) -> Board {
+    /// A board with the given `(x, y)` cells alive and the rest dead. Panics if a cell is outside
+    /// the board.
+    pub fn from_cells(
+        width: usize,
+        height: usize,
+        method: GOLMethod,
+        cells: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Board {
         let mut board = Self::dead_state(width, height, method);
-
-        for i in board.state.iter_mut().flatten() {
-            *i = random();
+        for (x, y) in cells {
+            board.set(x, y, true);
         }
 
         board
     }
 
+    /// A board filled from a soup, so the same soup always gives the same board
+    pub fn random_state(width: usize, height: usize, method: GOLMethod, soup: &Soup) -> Board {
+        Self::from_cells(width, height, method, soup.cells(width, height))
+    }
+
+    /// Switches the board to an isotropic non-totalistic rule
+    pub fn with_rule(mut self, rule: hensel::Rule) -> Board {
+        self.method = GOLMethod::Isotropic;
+        self.rule = rule;
+        self
+    }
+
+    /// Whether the cell at (x, y) is alive. Panics if it is outside the board.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.state[y][x]
+    }
+
+    /// Brings the cell at (x, y) to life or kills it. Panics if it is outside the board.
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        self.state[y][x] = alive;
+    }
+
+    /// The number of live cells
+    pub fn population(&self) -> usize {
+        self.state.iter().flatten().filter(|cell| **cell).count()
+    }
+
+    /// The `(x, y)` of every live cell, row by row from the top
+    pub fn live_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.state.iter().enumerate().flat_map(|(y, line)| {
+            line.iter()
+                .enumerate()
+                .filter(|(_, alive)| **alive)
+                .map(move |(x, _)| (x, y))
+        })
+    }
+
+    /// Advances the board a generation with its method
+    pub fn step(&mut self) {
+        *self = match self.method {
+            GOLMethod::Normal => self.next_state(),
+            GOLMethod::VonNeumann => self.next_state_neumann(),
+            GOLMethod::Dan => self.next_state_dan(),
+            GOLMethod::Isotropic => self.next_state_isotropic(),
+        };
+    }
+
+    /// Advances the board `generations` generations
+    pub fn step_n(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    /// The board followed by every generation after it, without end
+    pub fn generations(self) -> Generations {
+        Generations { next: self }
+    }
+
+    /// The smallest rectangle containing every live cell as `(left, top, right, bottom)`,
+    /// inclusive, or `None` for an empty board
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                if *cell {
+                    bounds = Some(match bounds {
+                        None => (x, y, x, y),
+                        Some((left, top, right, bottom)) => {
+                            (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                        }
+                    });
+                }
+            }
+        }
+
+        bounds
+    }
+
+    /// Hashes the whole board, equal boards hash to the same value
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.state.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Hashes the live cells relative to their bounding box, so a pattern hashes to the same value
+    /// wherever it is on the board. Also returns the top left corner of the bounding box.
+    pub fn pattern_hash(&self) -> (u64, (usize, usize)) {
+        let mut hasher = DefaultHasher::new();
+        let (left, top, right, bottom) = match self.bounding_box() {
+            Some(bounds) => bounds,
+            None => return (hasher.finish(), (0, 0)),
+        };
+
+        for line in &self.state[top..=bottom] {
+            line[left..=right].hash(&mut hasher);
+        }
+
+        (hasher.finish(), (left, top))
+    }
+
     pub fn render(&self) -> String {
         let mut string = String::new();
         let divider = "-".repeat(self.width + 2);
@@ -62,7 +180,7 @@ impl Board {
             for (x, cell) in line.iter().enumerate() {
                 let neighbors = self.calculate_num_neighbors(x, y);
                 if *cell {
-                    if neighbors < 2 || neighbors > 3 {
+                    if !(2..=3).contains(&neighbors) {
                         new_state[y][x] = false;
                     }
                 } else if neighbors == 3 {
@@ -76,14 +194,15 @@ impl Board {
             width: self.width,
             height: self.height,
             method: self.method,
+            rule: self.rule.clone(),
         }
     }
 
     fn calculate_num_neighbors(&self, x: usize, y: usize) -> u8 {
         let left = x != 0;
-        let right = x < self.width - 1;
+        let right = x + 1 < self.width;
         let up = y != 0;
-        let down = y < self.height - 1;
+        let down = y + 1 < self.height;
 
         let mut counter = 0;
 
@@ -128,7 +247,7 @@ impl Board {
             for (x, cell) in line.iter().enumerate() {
                 let neighbors = self.calculate_num_neighbors_neumann(x, y);
                 if *cell {
-                    if neighbors < 2 || neighbors > 3 {
+                    if !(2..=3).contains(&neighbors) {
                         new_state[y][x] = false;
                     }
                 } else if neighbors == 3 {
@@ -142,18 +261,19 @@ impl Board {
             width: self.width,
             height: self.height,
             method: self.method,
+            rule: self.rule.clone(),
         }
     }
 
     fn calculate_num_neighbors_neumann(&self, x: usize, y: usize) -> u8 {
         let left = x != 0;
-        let right = x < self.width - 1;
+        let right = x + 1 < self.width;
         let up = y != 0;
-        let down = y < self.height - 1;
+        let down = y + 1 < self.height;
         let more_left = left && x != 1;
-        let more_right = x < self.width - 2;
+        let more_right = x + 2 < self.width;
         let more_up = up && y != 1;
-        let more_down = y < self.height - 2;
+        let more_down = y + 2 < self.height;
 
         let mut counter = 0;
 
@@ -216,32 +336,155 @@ impl Board {
             width: self.width,
             height: self.height,
             method: self.method,
+            rule: self.rule.clone(),
         }
     }
 
-    pub fn load_from_file(filename: &str) -> std::io::Result<Board> {
-        let file = std::fs::read_to_string(filename)?;
-        let state = file.lines().fold(Vec::new(), |mut vec, line: &str| {
-            vec.push(line.chars().fold(Vec::new(), |mut acc, i| {
-                if i == '0' {
-                    acc.push(false);
+    pub fn next_state_isotropic(&self) -> Board {
+        let mut new_state = self.state.clone();
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                let neighborhood = self.calculate_neighborhood(x, y) as usize;
+                new_state[y][x] = if *cell {
+                    self.rule.survival[neighborhood]
                 } else {
-                    acc.push(true);
-                }
-                acc
-            }));
-            vec
-        });
+                    self.rule.birth[neighborhood]
+                };
+            }
+        }
 
-        Ok(Board {
-            state,
-            width: file.lines().collect::<Vec<&str>>()[0].len(),
-            height: file.lines().collect::<Vec<&str>>().len(),
-            method: GOLMethod::Normal,
+        Board {
+            state: new_state,
+            width: self.width,
+            height: self.height,
+            method: self.method,
+            rule: self.rule.clone(),
+        }
+    }
+
+    /// Packs the 8 neighbors of a cell into the bit layout used by `hensel::Rule`
+    fn calculate_neighborhood(&self, x: usize, y: usize) -> u8 {
+        let mut neighborhood = 0;
+
+        for (bit, (dx, dy)) in hensel::NEIGHBORS.iter().enumerate() {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if nx >= 0
+                && ny >= 0
+                && (nx as usize) < self.width
+                && (ny as usize) < self.height
+                && self.state[ny as usize][nx as usize]
+            {
+                neighborhood |= 1 << bit;
+            }
+        }
+
+        neighborhood
+    }
+
+    /// Loads a board written as lines of `0` for dead cells and `1` for live ones. Lines starting
+    /// with `#` are comments, and they and blank lines are skipped. Every line must be as long as
+    /// the first unless `pad` is set, when lines are filled out with dead cells to the longest.
+    pub fn load_from_file(filename: impl AsRef<Path>, pad: bool) -> Result<Board, GOLError> {
+        let path = filename.as_ref();
+        let file = std::fs::read_to_string(path).map_err(|err| GOLError::io(path, err))?;
+
+        parse(&file, pad).map_err(|(line, column, message)| GOLError::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message,
         })
     }
 }
 
+/// The generations of a board, starting with the board itself. Made by [`Board::generations`].
+#[derive(Debug, Clone)]
+pub struct Generations {
+    next: Board,
+}
+
+impl Iterator for Generations {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        let board = self.next.clone();
+        self.next.step();
+
+        Some(board)
+    }
+}
+
+/// The board in a file's text, or the line and column, counted from 1, of what's wrong with it
+fn parse(text: &str, pad: bool) -> Result<Board, (usize, usize, String)> {
+    let mut rows: Vec<(usize, Vec<bool>)> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(column, c)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err((
+                    number + 1,
+                    column + 1,
+                    format!("expected 0 or 1, found '{}'", c),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push((number + 1, row));
+    }
+
+    let Some((_, first)) = rows.first() else {
+        return Err((1, 1, "there are no cells".to_string()));
+    };
+    let width = match pad {
+        true => rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0),
+        false => first.len(),
+    };
+
+    for (number, row) in rows.iter_mut() {
+        if row.len() < width && !pad {
+            return Err((
+                *number,
+                row.len() + 1,
+                format!(
+                    "the line has {} cells where the first has {} (--pad fills short lines with dead cells)",
+                    row.len(),
+                    width
+                ),
+            ));
+        }
+        if row.len() > width {
+            return Err((
+                *number,
+                width + 1,
+                format!(
+                    "the line has {} cells where the first has {}",
+                    row.len(),
+                    width
+                ),
+            ));
+        }
+
+        row.resize(width, false);
+    }
+
+    Ok(Board {
+        height: rows.len(),
+        state: rows.into_iter().map(|(_, row)| row).collect(),
+        width,
+        method: GOLMethod::Normal,
+        rule: hensel::Rule::default(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -254,6 +497,7 @@ mod tests {
             width: 5,
             height: 5,
             method: crate::args::GOLMethod::Normal,
+            rule: Default::default(),
         };
 
         let expected_state = vec![vec![false; 5]; 5];
@@ -263,6 +507,45 @@ mod tests {
         assert_eq!(expected_state, next_state.state);
     }
 
+    #[test]
+    pub fn test_narrow_boards() {
+        for method in [
+            crate::args::GOLMethod::Normal,
+            crate::args::GOLMethod::VonNeumann,
+        ] {
+            // Lines of four lose their ends, and a lone cell dies
+            for (width, height, expected) in [
+                (1, 1, vec![false]),
+                (1, 4, vec![false, true, true, false]),
+                (4, 1, vec![false, true, true, false]),
+            ] {
+                let board = super::Board {
+                    state: vec![vec![true; width]; height],
+                    width,
+                    height,
+                    method,
+                    rule: Default::default(),
+                };
+
+                let next_state = board.next_state();
+
+                assert_eq!(expected, next_state.state.concat(), "{:?}", method);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_random_state() {
+        let soup = crate::soup::Soup::new(Some(7), 0.5, None);
+        let board = super::Board::random_state(20, 10, crate::args::GOLMethod::Normal, &soup);
+
+        assert_eq!(
+            board.state,
+            super::Board::random_state(20, 10, crate::args::GOLMethod::Normal, &soup).state
+        );
+        assert_eq!(soup.cells(20, 10).len(), board.population());
+    }
+
     #[test]
     pub fn test_birth_state() {
         let init_state = vec![
@@ -276,6 +559,7 @@ mod tests {
             width: 3,
             height: 3,
             method: crate::args::GOLMethod::Normal,
+            rule: Default::default(),
         };
 
         let expected_state = vec![
@@ -288,4 +572,105 @@ mod tests {
 
         assert_eq!(expected_state, next_state.state);
     }
+
+    #[test]
+    pub fn test_isotropic_state() {
+        let rule: crate::boards::hensel::Rule = "B2-a/S12".parse().unwrap();
+
+        // The middle cell sees two corners (2c) and is born
+        let board = super::Board {
+            state: vec![
+                vec![true, false, true],
+                vec![false, false, false],
+                vec![false, false, false],
+            ],
+            width: 3,
+            height: 3,
+            method: crate::args::GOLMethod::Isotropic,
+            rule: rule.clone(),
+        };
+
+        assert!(board.next_state_isotropic().state[1][1]);
+
+        // The middle cell sees a corner and its adjacent edge (2a) and stays dead
+        let board = super::Board {
+            state: vec![
+                vec![true, true, false],
+                vec![false, false, false],
+                vec![false, false, false],
+            ],
+            width: 3,
+            height: 3,
+            method: crate::args::GOLMethod::Isotropic,
+            rule,
+        };
+
+        assert!(!board.next_state_isotropic().state[1][1]);
+    }
+
+    #[test]
+    pub fn test_save_and_load() {
+        let mut board = super::Board::dead_state(4, 3, crate::args::GOLMethod::Normal);
+        board.set(1, 0, true);
+        board.set(3, 2, true);
+
+        let filename = std::env::temp_dir().join("gol-test-save-and-load.txt");
+        let filename = filename.to_str().unwrap();
+        let boards = crate::boards::Boards::GolBoard(board);
+        boards
+            .save_to_file(filename, &["seed 42, density 0.5".to_string()])
+            .unwrap();
+
+        let loaded = super::Board::load_from_file(filename, false).unwrap();
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!((4, 3), (loaded.width, loaded.height));
+        assert!(loaded.get(1, 0) && loaded.get(3, 2));
+        assert_eq!(2, loaded.population());
+    }
+
+    #[test]
+    pub fn test_cells_and_steps() {
+        let blinker = [(1, 0), (1, 1), (1, 2)];
+        let mut board = super::Board::from_cells(3, 3, crate::args::GOLMethod::Normal, blinker);
+        assert_eq!(blinker.to_vec(), board.live_cells().collect::<Vec<_>>());
+
+        board.step();
+        assert_eq!(
+            vec![(0, 1), (1, 1), (2, 1)],
+            board.live_cells().collect::<Vec<_>>()
+        );
+        board.step_n(3);
+        assert!(board.get(1, 0) && !board.get(0, 1));
+
+        let populations: Vec<usize> = board
+            .generations()
+            .take(3)
+            .map(|board| board.population())
+            .collect();
+        assert_eq!(vec![3, 3, 3], populations);
+    }
+
+    #[test]
+    pub fn test_parse() {
+        let board = super::parse("# a glider\n\n010\r\n001  \n  # spaced\n111\n", false).unwrap();
+        assert_eq!((3, 3), (board.width, board.height));
+        assert_eq!(5, board.population());
+
+        let error = |text| {
+            super::parse(text, false)
+                .err()
+                .map(|(line, column, _)| (line, column))
+        };
+        assert_eq!(Some((2, 2)), error("010\n0x1\n"));
+        assert_eq!(Some((3, 2)), error("010\n\n0\n"));
+        // Lines shorter and longer than the first
+        assert_eq!(Some((2, 3)), error("011\n01\n"));
+        assert_eq!(Some((2, 4)), error("010\n0101\n"));
+        assert_eq!(Some((1, 1)), error("# nothing\n\n"));
+
+        let padded = super::parse("1\n011\n01\n", true).unwrap();
+        assert_eq!((3, 3), (padded.width, padded.height));
+        assert!(padded.get(0, 0) && !padded.get(2, 0) && !padded.get(2, 2));
+    }
 }
@@ -0,0 +1,329 @@
+use std::{fmt, str::FromStr};
+
+use crate::GOLError;
+
+/// tlife
+pub const TLIFE: &str = "B3/S2-i34q";
+
+/// Looks up a named rule, falling back to treating the name as a rulestring
+pub fn preset(name: &str) -> &str {
+    match name.to_lowercase().as_str() {
+        "life" => "B3/S23",
+        "tlife" => TLIFE,
+        _ => name,
+    }
+}
+
+/// Hensel letters in the order they are printed
+const LETTERS: &str = "cekainyqjrtwz";
+
+/// One representative neighborhood for each letter of neighbor counts 1 to 4. Bits are the
+/// neighbors in reading order: NW, N, NE, W, E, SW, S, SE. Counts 5 to 7 use the complement of
+/// the same letter in count 8 - n.
+const REPRESENTATIVES: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 0b00000001), ('e', 0b00000010)],
+    &[
+        ('c', 0b00000101),
+        ('e', 0b00001010),
+        ('k', 0b00010001),
+        ('a', 0b00000011),
+        ('i', 0b00011000),
+        ('n', 0b00100100),
+    ],
+    &[
+        ('c', 0b00100101),
+        ('e', 0b00011010),
+        ('k', 0b00110010),
+        ('a', 0b00001011),
+        ('i', 0b00000111),
+        ('n', 0b00001101),
+        ('y', 0b00110001),
+        ('q', 0b00100110),
+        ('j', 0b00001110),
+        ('r', 0b00011001),
+    ],
+    &[
+        ('c', 0b10100101),
+        ('e', 0b01011010),
+        ('k', 0b00110011),
+        ('a', 0b00001111),
+        ('i', 0b00011101),
+        ('n', 0b00100111),
+        ('y', 0b00110101),
+        ('q', 0b00110110),
+        ('j', 0b00111010),
+        ('r', 0b00011011),
+        ('t', 0b00111001),
+        ('w', 0b00101110),
+        ('z', 0b00111100),
+    ],
+];
+
+/// Offsets of the neighbors in bit order
+pub const NEIGHBORS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Rotates a neighborhood a quarter turn clockwise
+fn rotate(neighborhood: u8) -> u8 {
+    map_bits(neighborhood, |(dx, dy)| (-dy, dx))
+}
+
+/// Mirrors a neighborhood left to right
+fn flip(neighborhood: u8) -> u8 {
+    map_bits(neighborhood, |(dx, dy)| (-dx, dy))
+}
+
+fn map_bits(neighborhood: u8, transform: impl Fn((isize, isize)) -> (isize, isize)) -> u8 {
+    let mut mapped = 0;
+    for (bit, offset) in NEIGHBORS.iter().enumerate() {
+        if neighborhood & (1 << bit) != 0 {
+            let target = NEIGHBORS
+                .iter()
+                .position(|n| *n == transform(*offset))
+                .unwrap();
+            mapped |= 1 << target;
+        }
+    }
+
+    mapped
+}
+
+/// Every neighborhood a neighborhood can be rotated or reflected into
+fn symmetries(neighborhood: u8) -> Vec<u8> {
+    let mut all = Vec::with_capacity(8);
+    let mut current = neighborhood;
+    for _ in 0..4 {
+        all.push(current);
+        all.push(flip(current));
+        current = rotate(current);
+    }
+    all.sort_unstable();
+    all.dedup();
+
+    all
+}
+
+/// The letters valid for a neighbor count along with their representative neighborhood
+fn letters(count: usize) -> Vec<(char, u8)> {
+    if count <= 4 {
+        REPRESENTATIVES[count].to_vec()
+    } else {
+        REPRESENTATIVES[8 - count]
+            .iter()
+            .map(|(letter, neighborhood)| (*letter, !neighborhood))
+            .collect()
+    }
+}
+
+/// The neighborhoods a letter of a neighbor count stands for
+fn letter_neighborhoods(count: usize, letter: char) -> Option<Vec<u8>> {
+    letters(count)
+        .into_iter()
+        .find(|(l, _)| *l == letter)
+        .map(|(_, neighborhood)| symmetries(neighborhood))
+}
+
+/// An isotropic non-totalistic rule in Hensel notation, e.g. `B2-a/S12`. Each half of the rule
+/// is a lookup table indexed by the 8 neighbors of a cell.
+#[derive(Clone)]
+pub struct Rule {
+    pub birth: Box<[bool; 256]>,
+    pub survival: Box<[bool; 256]>,
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        "B3/S23".parse().unwrap()
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rule({})", self)
+    }
+}
+
+impl PartialEq for Rule {
+    fn eq(&self, other: &Rule) -> bool {
+        self.birth == other.birth && self.survival == other.survival
+    }
+}
+
+impl Eq for Rule {}
+
+fn parse_half(half: &str, table: &mut [bool; 256]) -> Option<()> {
+    let mut chars = half.chars().peekable();
+    while let Some(digit) = chars.next() {
+        let count = digit.to_digit(10).filter(|count| *count <= 8)? as usize;
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut named = Vec::new();
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+            named.extend(letter_neighborhoods(count, letter.to_ascii_lowercase())?);
+        }
+        if negated && named.is_empty() {
+            return None;
+        }
+
+        for neighborhood in 0..=255u8 {
+            if neighborhood.count_ones() as usize == count
+                && (named.is_empty() || named.contains(&neighborhood) != negated)
+            {
+                table[neighborhood as usize] = true;
+            }
+        }
+    }
+
+    Some(())
+}
+
+impl FromStr for Rule {
+    type Err = GOLError;
+
+    fn from_str(s: &str) -> Result<Rule, GOLError> {
+        let invalid = || GOLError::InvalidRule(s.to_string());
+
+        let mut rule = Rule {
+            birth: Box::new([false; 256]),
+            survival: Box::new([false; 256]),
+        };
+
+        // Exactly one half of each kind, in either order
+        let (mut birth, mut survival) = (false, false);
+        for half in s.trim().split('/') {
+            let (table, seen) = match half.chars().next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => (&mut rule.birth, &mut birth),
+                Some('S') => (&mut rule.survival, &mut survival),
+                _ => return Err(invalid()),
+            };
+            if *seen {
+                return Err(invalid());
+            }
+            *seen = true;
+            parse_half(&half[1..], table).ok_or_else(invalid)?;
+        }
+        if !(birth && survival) {
+            return Err(invalid());
+        }
+
+        Ok(rule)
+    }
+}
+
+fn write_half(f: &mut fmt::Formatter, table: &[bool; 256]) -> fmt::Result {
+    for count in 0..=8 {
+        let letters = letters(count);
+        let included: Vec<char> = letters
+            .iter()
+            .filter(|(_, neighborhood)| table[*neighborhood as usize])
+            .map(|(letter, _)| *letter)
+            .collect();
+        let excluded: Vec<char> = letters
+            .iter()
+            .filter(|(_, neighborhood)| !table[*neighborhood as usize])
+            .map(|(letter, _)| *letter)
+            .collect();
+
+        if letters.is_empty() {
+            let neighborhood = if count == 0 { 0 } else { 255 };
+            if table[neighborhood] {
+                write!(f, "{}", count)?;
+            }
+        } else if excluded.is_empty() {
+            write!(f, "{}", count)?;
+        } else if included.is_empty() {
+            continue;
+        } else if included.len() <= excluded.len() {
+            write!(f, "{}", count)?;
+            write_letters(f, &included)?;
+        } else {
+            write!(f, "{}-", count)?;
+            write_letters(f, &excluded)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_letters(f: &mut fmt::Formatter, letters: &[char]) -> fmt::Result {
+    for letter in LETTERS.chars().filter(|l| letters.contains(l)) {
+        write!(f, "{}", letter)?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        write_half(f, &self.birth)?;
+        write!(f, "/S")?;
+        write_half(f, &self.survival)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rule;
+
+    #[test]
+    pub fn test_letters_partition_neighborhoods() {
+        let mut seen = [0; 256];
+        for count in 0..=8 {
+            let letters = super::letters(count);
+            if letters.is_empty() {
+                seen[if count == 0 { 0 } else { 255 }] += 1;
+            }
+            for (letter, _) in letters {
+                for neighborhood in super::letter_neighborhoods(count, letter).unwrap() {
+                    assert_eq!(count, neighborhood.count_ones() as usize);
+                    seen[neighborhood as usize] += 1;
+                }
+            }
+        }
+
+        assert!(seen.iter().all(|n| *n == 1));
+    }
+
+    #[test]
+    pub fn test_rule_round_trip() {
+        for rule in ["B3/S23", "B2-a/S12", super::TLIFE, "B2ce3-jr/S0", "B/S"] {
+            let parsed: Rule = rule.parse().unwrap();
+            assert_eq!(rule, parsed.to_string());
+        }
+
+        // A half given twice or left out
+        for rule in ["B3/B2", "S23/S1", "B3", "S23", "B3/S23/S1", ""] {
+            assert!(rule.parse::<Rule>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    pub fn test_totalistic_rule() {
+        let rule: Rule = "B3/S23".parse().unwrap();
+        for neighborhood in 0..=255u8 {
+            let count = neighborhood.count_ones();
+            assert_eq!(count == 3, rule.birth[neighborhood as usize]);
+            assert_eq!(
+                count == 2 || count == 3,
+                rule.survival[neighborhood as usize]
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_invalid_rules() {
+        for rule in ["B2z/S23", "B9/S23", "B3/X23", "B3-/S23"] {
+            assert!(rule.parse::<Rule>().is_err());
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use std::{fmt, str::FromStr};
+
+use crate::GOLError;
+
+/// Neighbors of a hexagonal cell on an even row, odd rows are shifted half a cell to the right
+const HEX_EVEN: [(isize, isize); 6] = [(-1, 0), (1, 0), (-1, -1), (0, -1), (-1, 1), (0, 1)];
+const HEX_ODD: [(isize, isize); 6] = [(-1, 0), (1, 0), (0, -1), (1, -1), (0, 1), (1, 1)];
+
+/// Neighbors of an upwards pointing triangle sharing an edge with it. A downwards pointing
+/// triangle uses the same offsets mirrored vertically.
+const TRI_EDGE: [(isize, isize); 3] = [(-1, 0), (1, 0), (0, 1)];
+/// Neighbors of an upwards pointing triangle sharing only a corner with it
+const TRI_VERTEX: [(isize, isize); 9] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-2, 0),
+    (2, 0),
+    (-2, 1),
+    (-1, 1),
+    (1, 1),
+    (2, 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The six neighbors of a hexagon, written `H`
+    Hex,
+    /// The three triangles sharing an edge, written `LE`
+    TriEdge,
+    /// The nine triangles sharing only a corner, written `LV`
+    TriVertex,
+    /// All twelve triangles sharing an edge or a corner, written `L`
+    Tri,
+}
+
+impl Neighborhood {
+    fn suffix(self) -> &'static str {
+        match self {
+            Neighborhood::Hex => "H",
+            Neighborhood::TriEdge => "LE",
+            Neighborhood::TriVertex => "LV",
+            Neighborhood::Tri => "L",
+        }
+    }
+
+    pub fn is_hex(self) -> bool {
+        self == Neighborhood::Hex
+    }
+
+    /// The number of neighbors of each cell
+    fn size(self) -> u32 {
+        match self {
+            Neighborhood::Hex => 6,
+            Neighborhood::TriEdge => 3,
+            Neighborhood::TriVertex => 9,
+            Neighborhood::Tri => 12,
+        }
+    }
+}
+
+/// An outer totalistic rule on a hexagonal or triangular grid, e.g. `B2/S34H`. Each count is a
+/// digit, unless one of them is above 9, when they are separated by commas as in `B4/S3,4,10L`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Bit n is set when a dead cell with n live neighbors is born
+    pub birth: u16,
+    /// Bit n is set when a live cell with n live neighbors survives
+    pub survival: u16,
+    pub neighborhood: Neighborhood,
+}
+
+impl Rule {
+    pub fn default_hex() -> Rule {
+        "B2/S34H".parse().unwrap()
+    }
+
+    pub fn default_tri() -> Rule {
+        "B4/S345L".parse().unwrap()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = GOLError;
+
+    fn from_str(s: &str) -> Result<Rule, GOLError> {
+        let invalid = || GOLError::InvalidRule(s.to_string());
+
+        let s = s.trim().to_uppercase();
+        let (counts, neighborhood) = [
+            Neighborhood::TriEdge,
+            Neighborhood::TriVertex,
+            Neighborhood::Tri,
+            Neighborhood::Hex,
+        ]
+        .iter()
+        .find_map(|n| s.strip_suffix(n.suffix()).map(|counts| (counts, *n)))
+        .ok_or_else(invalid)?;
+
+        let mut rule = Rule {
+            birth: 0,
+            survival: 0,
+            neighborhood,
+        };
+
+        // Exactly one half of each kind, in either order
+        let (mut birth, mut survival) = (false, false);
+        for half in counts.split('/') {
+            let mut chars = half.chars();
+            let (mask, seen) = match chars.next() {
+                Some('B') => (&mut rule.birth, &mut birth),
+                Some('S') => (&mut rule.survival, &mut survival),
+                _ => return Err(invalid()),
+            };
+            if *seen {
+                return Err(invalid());
+            }
+            *seen = true;
+            let counts = chars.as_str();
+            let counts: Vec<u32> = match counts.contains(',') {
+                true => counts
+                    .split(',')
+                    .map(|count| count.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?,
+                false => counts
+                    .chars()
+                    .map(|digit| digit.to_digit(10).ok_or_else(invalid))
+                    .collect::<Result<_, _>>()?,
+            };
+            for count in counts {
+                if count > neighborhood.size() {
+                    return Err(invalid());
+                }
+                *mask |= 1 << count;
+            }
+        }
+        if !(birth && survival) {
+            return Err(invalid());
+        }
+
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = |mask: u16| -> String {
+            let counts: Vec<String> = (0..16)
+                .filter(|n| mask & (1 << n) != 0)
+                .map(|n: u32| n.to_string())
+                .collect();
+            match mask >> 10 {
+                0 => counts.concat(),
+                _ => counts.join(","),
+            }
+        };
+
+        write!(
+            f,
+            "B{}/S{}{}",
+            digits(self.birth),
+            digits(self.survival),
+            self.neighborhood.suffix()
+        )
+    }
+}
+
+type StateArray = Vec<Vec<bool>>;
+
+/// A board of hexagonal or triangular cells stored in a rectangular array. Hexagons use offset
+/// rows, triangles alternate between pointing up and down along each row.
+#[derive(Debug, Clone)]
+pub struct Board {
+    state: StateArray,
+    pub width: usize,
+    pub height: usize,
+    pub rule: Rule,
+}
+
+impl Board {
+    pub fn dead_state(width: usize, height: usize, rule: Rule) -> Board {
+        let state = vec![vec![false; width]; height];
+
+        Board {
+            state,
+            width,
+            height,
+            rule,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.state[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        self.state[y][x] = alive;
+    }
+
+    fn points_up(x: usize, y: usize) -> bool {
+        (x + y).is_multiple_of(2)
+    }
+
+    fn neighbor_offsets(&self, x: usize, y: usize) -> Vec<(isize, isize)> {
+        let flip = |offsets: &[(isize, isize)]| -> Vec<(isize, isize)> {
+            if Self::points_up(x, y) {
+                offsets.to_vec()
+            } else {
+                offsets.iter().map(|(dx, dy)| (*dx, -dy)).collect()
+            }
+        };
+
+        match self.rule.neighborhood {
+            Neighborhood::Hex if y.is_multiple_of(2) => HEX_EVEN.to_vec(),
+            Neighborhood::Hex => HEX_ODD.to_vec(),
+            Neighborhood::TriEdge => flip(&TRI_EDGE),
+            Neighborhood::TriVertex => flip(&TRI_VERTEX),
+            Neighborhood::Tri => [flip(&TRI_EDGE), flip(&TRI_VERTEX)].concat(),
+        }
+    }
+
+    fn calculate_num_neighbors(&self, x: usize, y: usize) -> u32 {
+        self.neighbor_offsets(x, y)
+            .iter()
+            .filter(|(dx, dy)| {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                nx >= 0
+                    && ny >= 0
+                    && (nx as usize) < self.width
+                    && (ny as usize) < self.height
+                    && self.state[ny as usize][nx as usize]
+            })
+            .count() as u32
+    }
+
+    pub fn next_state(&self) -> Board {
+        let mut new_state = self.state.clone();
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                let neighbors = self.calculate_num_neighbors(x, y);
+                let mask = if *cell {
+                    self.rule.survival
+                } else {
+                    self.rule.birth
+                };
+                new_state[y][x] = mask & (1 << neighbors) != 0;
+            }
+        }
+
+        Board {
+            state: new_state,
+            width: self.width,
+            height: self.height,
+            rule: self.rule,
+        }
+    }
+
+    /// The column and line cell (x, y) is drawn at by `render`
+    pub fn screen_position(&self, x: usize, y: usize) -> (usize, usize) {
+        if self.rule.neighborhood.is_hex() {
+            (1 + 2 * x + y % 2, y + 1)
+        } else {
+            (x + 1, y + 1)
+        }
+    }
+
+    /// Renders hexagons two columns wide with every odd row shifted by one column, and triangles
+    /// as one column each pointing up or down
+    pub fn render(&self) -> String {
+        let hex = self.rule.neighborhood.is_hex();
+        let inner_width = if hex { 2 * self.width + 1 } else { self.width };
+
+        let mut string = String::new();
+        let divider = "-".repeat(inner_width + 2);
+        string.push_str(&divider);
+        string.push('\n');
+        for (y, line) in self.state.iter().enumerate() {
+            string.push('|');
+            if hex && y % 2 == 1 {
+                string.push(' ');
+            }
+            for (x, cell) in line.iter().enumerate() {
+                if hex {
+                    string.push(if *cell { '#' } else { '.' });
+                    string.push(' ');
+                } else {
+                    string.push(match (*cell, Self::points_up(x, y)) {
+                        (true, true) => '▲',
+                        (true, false) => '▼',
+                        (false, true) => '△',
+                        (false, false) => '▽',
+                    });
+                }
+            }
+            if hex && y % 2 == 0 {
+                string.push(' ');
+            }
+            string.push('|');
+            string.push('\n');
+        }
+        string.push_str(&divider);
+
+        string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rule;
+
+    #[test]
+    pub fn test_rule_round_trip() {
+        for rule in [
+            "B2/S34H",
+            "B4/S345L",
+            "B1/S12LE",
+            "B35/S2LV",
+            "B4/S3,4,10,12L",
+        ] {
+            let parsed: Rule = rule.parse().unwrap();
+            assert_eq!(rule, parsed.to_string());
+        }
+
+        assert_eq!(
+            "B4/S345L".parse::<Rule>().unwrap(),
+            "B4/S3,4,5L".parse::<Rule>().unwrap()
+        );
+        assert!("B3/S23".parse::<Rule>().is_err());
+        assert!("B2/S37H".parse::<Rule>().is_err());
+        assert!("B4/S3,13L".parse::<Rule>().is_err());
+        assert!("B4/S3,,4L".parse::<Rule>().is_err());
+        // A half given twice or left out
+        for rule in ["B2/B3H", "S34H", "B2H", "S3/S4L", "B2/S34/S5H"] {
+            assert!(rule.parse::<Rule>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    pub fn test_neighbor_counts() {
+        let board = super::Board::dead_state(10, 10, "B/SL".parse().unwrap());
+        for (x, y) in [(4, 4), (5, 4)] {
+            let offsets = board.neighbor_offsets(x, y);
+            assert_eq!(12, offsets.len());
+            // Neighbors are symmetric: each neighbor lists this cell as its own neighbor
+            for (dx, dy) in offsets {
+                let (nx, ny) = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+                assert!(board.neighbor_offsets(nx, ny).contains(&(-dx, -dy)));
+            }
+        }
+
+        let board = super::Board::dead_state(10, 10, Rule::default_hex());
+        for (x, y) in [(4, 4), (4, 5)] {
+            for (dx, dy) in board.neighbor_offsets(x, y) {
+                let (nx, ny) = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+                assert!(board.neighbor_offsets(nx, ny).contains(&(-dx, -dy)));
+            }
+        }
+    }
+}
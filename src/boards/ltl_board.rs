@@ -0,0 +1,364 @@
+use std::{fmt, str::FromStr};
+
+use crate::GOLError;
+
+/// The largest range a rule can have, as neighborhoods grow with its square
+const MAX_RADIUS: usize = 500;
+
+/// Bosco's Rule
+pub const BOSCO: &str = "R5,C0,M1,S34..58,B34..45,NM";
+/// Majority
+pub const MAJORITY: &str = "R4,C0,M1,S41..81,B41..81,NM";
+
+/// Looks up a named rule, falling back to treating the name as a rulestring
+pub fn preset(name: &str) -> &str {
+    match name.to_lowercase().as_str() {
+        "bosco" => BOSCO,
+        "majority" => MAJORITY,
+        _ => name,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Every cell within the (2r+1)x(2r+1) square
+    Moore,
+    /// Every cell within Manhattan distance r
+    VonNeumann,
+    /// Every cell within Euclidean distance r
+    Circular,
+    /// Cells of the (2r+1)x(2r+1) square given row by row, the middle cell is ignored
+    Custom(Vec<bool>),
+}
+
+/// A Larger than Life rule in Golly's notation, e.g. `R5,C0,M1,S34..58,B34..45,NM`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub radius: usize,
+    /// Number of cell states, cells above 1 are dying and count as dead
+    pub states: u8,
+    /// Whether a cell counts itself as a neighbor
+    pub middle: bool,
+    pub survival: (u32, u32),
+    pub birth: (u32, u32),
+    pub neighborhood: Neighborhood,
+}
+
+impl Rule {
+    fn contains(&self, dx: isize, dy: isize) -> bool {
+        let r = self.radius as isize;
+        match &self.neighborhood {
+            Neighborhood::Moore => true,
+            Neighborhood::VonNeumann => dx.abs() + dy.abs() <= r,
+            Neighborhood::Circular => dx * dx + dy * dy <= r * r,
+            Neighborhood::Custom(mask) => {
+                let side = 2 * r + 1;
+                (dx, dy) == (0, 0) || mask[((dy + r) * side + dx + r) as usize]
+            }
+        }
+    }
+
+    /// Splits the neighborhood (including the middle cell) into rectangles given as
+    /// `(x0, y0, x1, y1)` offsets, so each one can be counted with a single summed-area lookup
+    fn rectangles(&self) -> Vec<(isize, isize, isize, isize)> {
+        let r = self.radius as isize;
+
+        if self.neighborhood == Neighborhood::Moore {
+            return vec![(-r, -r, r, r)];
+        }
+
+        let mut rectangles = Vec::new();
+        for dy in -r..=r {
+            let mut start = None;
+            for dx in -r..=r + 1 {
+                match (start, dx <= r && self.contains(dx, dy)) {
+                    (None, true) => start = Some(dx),
+                    (Some(x0), false) => {
+                        rectangles.push((x0, dy, dx - 1, dy));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        rectangles
+    }
+}
+
+impl FromStr for Rule {
+    type Err = GOLError;
+
+    fn from_str(s: &str) -> Result<Rule, GOLError> {
+        let invalid = || GOLError::InvalidRule(s.to_string());
+
+        let parse_range = |range: &str| -> Result<(u32, u32), GOLError> {
+            let (min, max) = range.split_once("..").ok_or_else(invalid)?;
+            Ok((
+                min.parse().map_err(|_| invalid())?,
+                max.parse().map_err(|_| invalid())?,
+            ))
+        };
+
+        let mut radius = None;
+        let mut states = 2;
+        let mut middle = false;
+        let mut survival = None;
+        let mut birth = None;
+        let mut neighborhood = String::from("M");
+
+        for part in s.trim().split(',') {
+            let mut chars = part.trim().chars();
+            let key = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+            let value = chars.as_str();
+            match key {
+                'R' => radius = Some(value.parse::<usize>().map_err(|_| invalid())?),
+                'C' => states = value.parse::<u8>().map_err(|_| invalid())?.max(2),
+                'M' => {
+                    middle = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    }
+                }
+                'S' => survival = Some(parse_range(value)?),
+                'B' => birth = Some(parse_range(value)?),
+                'N' => neighborhood = value.to_string(),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let radius = radius
+            .filter(|r| (1..=MAX_RADIUS).contains(r))
+            .ok_or_else(invalid)?;
+        let side = 2 * radius + 1;
+
+        let neighborhood = match neighborhood.to_uppercase().as_str() {
+            "M" => Neighborhood::Moore,
+            "N" => Neighborhood::VonNeumann,
+            "C" => Neighborhood::Circular,
+            custom => {
+                let hex = custom.strip_prefix('@').ok_or_else(invalid)?;
+                let mut mask = Vec::with_capacity(hex.len() * 4);
+                for digit in hex.chars() {
+                    let value = digit.to_digit(16).ok_or_else(invalid)?;
+                    mask.extend((0..4).rev().map(|bit| value & (1 << bit) != 0));
+                }
+                if mask.len() < side * side {
+                    return Err(invalid());
+                }
+                mask.truncate(side * side);
+                Neighborhood::Custom(mask)
+            }
+        };
+
+        Ok(Rule {
+            radius,
+            states,
+            middle,
+            survival: survival.ok_or_else(invalid)?,
+            birth: birth.ok_or_else(invalid)?,
+            neighborhood,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},N",
+            self.radius,
+            if self.states == 2 { 0 } else { self.states },
+            self.middle as u8,
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1
+        )?;
+
+        match &self.neighborhood {
+            Neighborhood::Moore => write!(f, "M"),
+            Neighborhood::VonNeumann => write!(f, "N"),
+            Neighborhood::Circular => write!(f, "C"),
+            Neighborhood::Custom(mask) => {
+                write!(f, "@")?;
+                for nibble in mask.chunks(4) {
+                    let value = nibble
+                        .iter()
+                        .chain(std::iter::repeat(&false))
+                        .take(4)
+                        .fold(0, |acc, bit| acc << 1 | *bit as u32);
+                    write!(f, "{:x}", value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+type StateArray = Vec<Vec<u8>>;
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    state: StateArray,
+    pub width: usize,
+    pub height: usize,
+    pub rule: Rule,
+}
+
+impl Board {
+    pub fn dead_state(width: usize, height: usize, rule: Rule) -> Board {
+        let state = vec![vec![0; width]; height];
+
+        Board {
+            state,
+            width,
+            height,
+            rule,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.state[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, state: u8) {
+        self.state[y][x] = state % self.rule.states;
+    }
+
+    pub fn render(&self) -> String {
+        let mut string = String::new();
+        let divider = "-".repeat(self.width + 2);
+        string.push_str(&divider);
+        string.push('\n');
+        for y in &self.state {
+            string.push('|');
+            for x in y {
+                string.push(match x {
+                    0 => ' ',
+                    1 => '#',
+                    _ => '.',
+                });
+            }
+            string.push('|');
+            string.push('\n');
+        }
+        string.push_str(&divider);
+
+        string
+    }
+
+    /// Builds a summed-area table of live cells with a leading row and column of zeros
+    fn summed_area_table(&self) -> Vec<Vec<u32>> {
+        let mut table = vec![vec![0; self.width + 1]; self.height + 1];
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                table[y + 1][x + 1] =
+                    (*cell == 1) as u32 + table[y][x + 1] + table[y + 1][x] - table[y][x];
+            }
+        }
+
+        table
+    }
+
+    pub fn next_state(&self) -> Board {
+        let table = self.summed_area_table();
+        let rectangles = self.rule.rectangles();
+        let (width, height) = (self.width as isize, self.height as isize);
+
+        let mut new_state = self.state.clone();
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                let mut neighbors = 0;
+                for (x0, y0, x1, y1) in &rectangles {
+                    let left = (x as isize + x0).clamp(0, width) as usize;
+                    let right = (x as isize + x1 + 1).clamp(0, width) as usize;
+                    let top = (y as isize + y0).clamp(0, height) as usize;
+                    let bottom = (y as isize + y1 + 1).clamp(0, height) as usize;
+                    neighbors += table[bottom][right] + table[top][left]
+                        - table[top][right]
+                        - table[bottom][left];
+                }
+
+                if !self.rule.middle && *cell == 1 {
+                    neighbors -= 1;
+                }
+
+                new_state[y][x] = match *cell {
+                    0 if (self.rule.birth.0..=self.rule.birth.1).contains(&neighbors) => 1,
+                    0 => 0,
+                    1 if (self.rule.survival.0..=self.rule.survival.1).contains(&neighbors) => 1,
+                    dying => (dying + 1) % self.rule.states,
+                };
+            }
+        }
+
+        Board {
+            state: new_state,
+            width: self.width,
+            height: self.height,
+            rule: self.rule.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rule;
+
+    #[test]
+    pub fn test_rule_round_trip() {
+        for rule in [
+            super::BOSCO,
+            super::MAJORITY,
+            "R2,C3,M0,S1..4,B2..3,NC",
+            "R1,C0,M0,S2..3,B3..3,N@fa8",
+        ] {
+            let parsed: Rule = rule.parse().unwrap();
+            assert_eq!(rule, parsed.to_string());
+        }
+
+        for rule in [
+            "R0,C0,M0,S2..3,B3..3,NM",
+            "R501,C0,M0,S2..3,B3..3,NM",
+            "R18446744073709551615,C0,M0,S2..3,B3..3,NM",
+            "R1,C0,M2,S2..3,B3..3,NM",
+            "R1,C0,Myes,S2..3,B3..3,NM",
+        ] {
+            assert!(rule.parse::<Rule>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    pub fn test_radius_one_matches_life() {
+        let rule: Rule = "R1,C0,M0,S2..3,B3..3,NM".parse().unwrap();
+        let mut board = super::Board::dead_state(5, 5, rule);
+        for y in 1..4 {
+            board.set(2, y, 1);
+        }
+
+        let next_state = board.next_state();
+
+        let mut expected = vec![vec![0; 5]; 5];
+        expected[2] = vec![0, 1, 1, 1, 0];
+        assert_eq!(expected, next_state.state);
+    }
+
+    #[test]
+    pub fn test_neighborhood_counts() {
+        for rule in ["R3,C0,M1,S0..0,B0..0,NN", "R3,C0,M1,S0..0,B0..0,NC"] {
+            let rule: Rule = rule.parse().unwrap();
+            let cells: usize = rule
+                .rectangles()
+                .iter()
+                .map(|(x0, y0, x1, y1)| ((x1 - x0 + 1) * (y1 - y0 + 1)) as usize)
+                .sum();
+            let expected = (-3isize..=3)
+                .flat_map(|dy| (-3isize..=3).map(move |dx| (dx, dy)))
+                .filter(|(dx, dy)| rule.contains(*dx, *dy))
+                .count();
+            assert_eq!(expected, cells);
+        }
+    }
+}
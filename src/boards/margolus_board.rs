@@ -0,0 +1,246 @@
+/// Bits of a 2x2 block: top left, top right, bottom left, bottom right
+const TOP_LEFT: u8 = 0b0001;
+const TOP_RIGHT: u8 = 0b0010;
+const BOTTOM_LEFT: u8 = 0b0100;
+const BOTTOM_RIGHT: u8 = 0b1000;
+
+fn rotate_half_turn(block: u8) -> u8 {
+    (block & TOP_LEFT) << 3 | (block & TOP_RIGHT) << 1 | (block & BOTTOM_LEFT) >> 1 | block >> 3
+}
+
+/// A block rule maps each of the 16 possible 2x2 blocks to its replacement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub table: [u8; 16],
+}
+
+impl Rule {
+    fn from_fn(f: impl Fn(u8) -> u8) -> Rule {
+        let mut table = [0; 16];
+        for (block, entry) in table.iter_mut().enumerate() {
+            *entry = f(block as u8);
+        }
+
+        Rule { table }
+    }
+
+    /// Blocks with two live cells are kept, all others are inverted and blocks that had three
+    /// live cells are also turned around
+    pub fn critters() -> Rule {
+        Rule::from_fn(|block| match block.count_ones() {
+            2 => block,
+            3 => rotate_half_turn(!block & 0xF),
+            _ => !block & 0xF,
+        })
+    }
+
+    /// Full and empty blocks are inverted
+    pub fn tron() -> Rule {
+        Rule::from_fn(|block| match block {
+            0 | 0xF => !block & 0xF,
+            _ => block,
+        })
+    }
+
+    /// The billiard ball machine: single balls move diagonally through the block and two balls
+    /// colliding head on leave along the other diagonal
+    pub fn billiard_ball() -> Rule {
+        Rule::from_fn(|block| match block {
+            TOP_LEFT | TOP_RIGHT | BOTTOM_LEFT | BOTTOM_RIGHT => rotate_half_turn(block),
+            0b1001 => 0b0110,
+            0b0110 => 0b1001,
+            _ => block,
+        })
+    }
+
+    /// Grains fall down when the cell below is empty and slide off the top of a stack
+    pub fn sand() -> Rule {
+        Rule::from_fn(|block| {
+            let mut block = block;
+            for (top, bottom) in [(TOP_LEFT, BOTTOM_LEFT), (TOP_RIGHT, BOTTOM_RIGHT)] {
+                if block & top != 0 && block & bottom == 0 {
+                    block ^= top | bottom;
+                }
+            }
+            for (top, bottom, other_top, other_bottom) in [
+                (TOP_LEFT, BOTTOM_LEFT, TOP_RIGHT, BOTTOM_RIGHT),
+                (TOP_RIGHT, BOTTOM_RIGHT, TOP_LEFT, BOTTOM_LEFT),
+            ] {
+                if block & (top | bottom) == top | bottom && block & (other_top | other_bottom) == 0
+                {
+                    block ^= top | other_bottom;
+                }
+            }
+            block
+        })
+    }
+
+    /// The rule undoing this one, if every block has exactly one predecessor
+    pub fn inverse(&self) -> Option<Rule> {
+        let mut table = [None; 16];
+        for (block, next) in self.table.iter().enumerate() {
+            if table[*next as usize].replace(block as u8).is_some() {
+                return None;
+            }
+        }
+
+        Some(Rule {
+            table: table.map(|block| block.unwrap()),
+        })
+    }
+}
+
+type StateArray = Vec<Vec<bool>>;
+
+/// A board partitioned into 2x2 blocks, with the partition shifted diagonally by one cell every
+/// other generation. Cells on the edge that don't fill a whole block are left unchanged.
+#[derive(Debug, Clone)]
+pub struct Board {
+    state: StateArray,
+    pub width: usize,
+    pub height: usize,
+    pub rule: Rule,
+    /// Whether the next generation uses the shifted partition
+    odd_phase: bool,
+}
+
+impl Board {
+    pub fn dead_state(width: usize, height: usize, rule: Rule) -> Board {
+        let state = vec![vec![false; width]; height];
+
+        Board {
+            state,
+            width,
+            height,
+            rule,
+            odd_phase: false,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.state[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        self.state[y][x] = alive;
+    }
+
+    pub fn render(&self) -> String {
+        let mut string = String::new();
+        let divider = "-".repeat(self.width + 2);
+        string.push_str(&divider);
+        string.push('\n');
+        for y in &self.state {
+            string.push('|');
+            for x in y {
+                if *x {
+                    string.push('#');
+                } else {
+                    string.push(' ');
+                }
+            }
+            string.push('|');
+            string.push('\n');
+        }
+        string.push_str(&divider);
+
+        string
+    }
+
+    fn apply(&self, table: &[u8; 16], odd_phase: bool) -> StateArray {
+        let offset = odd_phase as usize;
+        let mut new_state = self.state.clone();
+
+        for top in (offset..self.height.saturating_sub(1)).step_by(2) {
+            for left in (offset..self.width.saturating_sub(1)).step_by(2) {
+                let cells = [
+                    (left, top),
+                    (left + 1, top),
+                    (left, top + 1),
+                    (left + 1, top + 1),
+                ];
+
+                let block = cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (x, y))| self.state[*y][*x])
+                    .fold(0, |acc, (bit, _)| acc | 1 << bit);
+
+                let next = table[block];
+                for (bit, (x, y)) in cells.iter().enumerate() {
+                    new_state[*y][*x] = next & (1 << bit) != 0;
+                }
+            }
+        }
+
+        new_state
+    }
+
+    pub fn next_state(&self) -> Board {
+        Board {
+            state: self.apply(&self.rule.table, self.odd_phase),
+            width: self.width,
+            height: self.height,
+            rule: self.rule.clone(),
+            odd_phase: !self.odd_phase,
+        }
+    }
+
+    /// Steps back one generation, if the rule is reversible
+    pub fn previous_state(&self) -> Option<Board> {
+        let inverse = self.rule.inverse()?;
+
+        Some(Board {
+            state: self.apply(&inverse.table, !self.odd_phase),
+            width: self.width,
+            height: self.height,
+            rule: self.rule.clone(),
+            odd_phase: !self.odd_phase,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Board, Rule};
+    use crate::soup::Soup;
+
+    #[test]
+    pub fn test_reversible_presets() {
+        assert!(Rule::critters().inverse().is_some());
+        assert!(Rule::tron().inverse().is_some());
+        assert!(Rule::billiard_ball().inverse().is_some());
+        assert!(Rule::sand().inverse().is_none());
+    }
+
+    #[test]
+    pub fn test_rewind() {
+        let mut start = Board::dead_state(12, 8, Rule::critters());
+        for (x, y) in Soup::new(Some(1), 0.5, None).cells(12, 8) {
+            start.set(x, y, true);
+        }
+
+        let mut board = start.clone();
+        for _ in 0..5 {
+            board = board.next_state();
+        }
+        for _ in 0..5 {
+            board = board.previous_state().unwrap();
+        }
+
+        assert_eq!(start.state, board.state);
+        assert_eq!(start.odd_phase, board.odd_phase);
+    }
+
+    #[test]
+    pub fn test_sand_falls() {
+        let mut board = Board::dead_state(4, 4, Rule::sand());
+        board.set(1, 0, true);
+
+        for _ in 0..4 {
+            board = board.next_state();
+        }
+
+        assert!(board.get(1, 3));
+    }
+}
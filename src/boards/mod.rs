@@ -1,7 +1,236 @@
 pub mod ant_board;
 pub mod gol_board;
+pub mod hensel;
+pub mod lattice_board;
+pub mod ltl_board;
+pub mod margolus_board;
+pub mod wire_board;
 
 pub enum Boards {
     GolBoard(gol_board::Board),
     AntBoard(ant_board::Board),
+    WireBoard(wire_board::Board),
+    LtlBoard(ltl_board::Board),
+    LatticeBoard(lattice_board::Board),
+    MargolusBoard(margolus_board::Board),
+}
+
+impl Boards {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Boards::GolBoard(_) => "Conway's Game of Life",
+            Boards::AntBoard(_) => "Langton's Ant",
+            Boards::WireBoard(_) => "Wireworld",
+            Boards::LtlBoard(_) => "Larger than Life",
+            Boards::LatticeBoard(lattice) if lattice.rule.neighborhood.is_hex() => "Hexagonal Life",
+            Boards::LatticeBoard(_) => "Triangular Life",
+            Boards::MargolusBoard(_) => "Margolus Block Automaton",
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        match self {
+            Boards::GolBoard(gol) => (gol.width, gol.height),
+            Boards::AntBoard(ant) => (ant.width, ant.height),
+            Boards::WireBoard(wire) => (wire.width, wire.height),
+            Boards::LtlBoard(ltl) => (ltl.width, ltl.height),
+            Boards::LatticeBoard(lattice) => (lattice.width, lattice.height),
+            Boards::MargolusBoard(margolus) => (margolus.width, margolus.height),
+        }
+    }
+
+    /// The number of columns and lines `render` uses for the cells, without the border
+    pub fn screen_size(&self) -> (usize, usize) {
+        match self {
+            Boards::LatticeBoard(lattice) if lattice.rule.neighborhood.is_hex() => {
+                (2 * lattice.width + 1, lattice.height)
+            }
+            _ => self.size(),
+        }
+    }
+
+    /// The column and line of `render`'s output that cell (x, y) is drawn at
+    pub fn screen_position(&self, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Boards::LatticeBoard(lattice) => lattice.screen_position(x, y),
+            _ => (x + 1, y + 1),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        match self {
+            Boards::GolBoard(gol) => gol.render(),
+            Boards::AntBoard(ant) => ant.render(),
+            Boards::WireBoard(wire) => wire.render(),
+            Boards::LtlBoard(ltl) => ltl.render(),
+            Boards::LatticeBoard(lattice) => lattice.render(),
+            Boards::MargolusBoard(margolus) => margolus.render(),
+        }
+    }
+
+    pub fn update(&mut self) {
+        match self {
+            Boards::GolBoard(board) => board.step(),
+            Boards::AntBoard(board) => *board = board.update(),
+            Boards::WireBoard(board) => *board = board.next_state(),
+            Boards::LtlBoard(board) => *board = board.next_state(),
+            Boards::LatticeBoard(board) => *board = board.next_state(),
+            Boards::MargolusBoard(board) => *board = board.next_state(),
+        }
+    }
+
+    /// Steps back one generation on boards with a reversible rule, returning whether it could
+    pub fn rewind(&mut self) -> bool {
+        match self {
+            Boards::MargolusBoard(board) => match board.previous_state() {
+                Some(previous) => {
+                    *board = previous;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the cell at (x, y) is in a state other than the background one: live cells, black
+    /// cells for the ant and electron heads in Wireworld
+    pub fn alive(&self, x: usize, y: usize) -> bool {
+        match self {
+            Boards::GolBoard(gol) => gol.get(x, y),
+            Boards::AntBoard(ant) => ant.get(x, y),
+            Boards::WireBoard(wire) => wire.get(x, y) == wire_board::Cell::Head,
+            Boards::LtlBoard(ltl) => ltl.get(x, y) != 0,
+            Boards::LatticeBoard(lattice) => lattice.get(x, y),
+            Boards::MargolusBoard(margolus) => margolus.get(x, y),
+        }
+    }
+
+    /// Whether the cell at (x, y) is drawn as a live or a dead cell, or `None` for cells with a
+    /// glyph of their own: the ant, Wireworld cells, decaying Larger than Life cells and triangles
+    pub fn live_glyph(&self, x: usize, y: usize) -> Option<bool> {
+        match self {
+            Boards::GolBoard(gol) => Some(gol.get(x, y)),
+            Boards::AntBoard(ant) if (ant.ant().0, ant.ant().1) == (x, y) => None,
+            Boards::AntBoard(ant) => Some(ant.get(x, y)),
+            Boards::WireBoard(_) => None,
+            Boards::LtlBoard(ltl) => match ltl.get(x, y) {
+                0 => Some(false),
+                1 => Some(true),
+                _ => None,
+            },
+            Boards::LatticeBoard(lattice) if lattice.rule.neighborhood.is_hex() => {
+                Some(lattice.get(x, y))
+            }
+            Boards::LatticeBoard(_) => None,
+            Boards::MargolusBoard(margolus) => Some(margolus.get(x, y)),
+        }
+    }
+
+    /// The state of the cell at (x, y) as a number, 0 being the background state
+    pub fn state(&self, x: usize, y: usize) -> u8 {
+        match self {
+            Boards::GolBoard(gol) => gol.get(x, y) as u8,
+            Boards::AntBoard(ant) => ant.get(x, y) as u8,
+            Boards::WireBoard(wire) => wire.get(x, y) as u8,
+            Boards::LtlBoard(ltl) => ltl.get(x, y),
+            Boards::LatticeBoard(lattice) => lattice.get(x, y) as u8,
+            Boards::MargolusBoard(margolus) => margolus.get(x, y) as u8,
+        }
+    }
+
+    /// Puts the cell at (x, y) back in a state given by `state`
+    pub fn set_state(&mut self, x: usize, y: usize, state: u8) {
+        match self {
+            Boards::GolBoard(gol) => gol.set(x, y, state != 0),
+            Boards::AntBoard(ant) => ant.set(x, y, state != 0),
+            Boards::WireBoard(wire) => {
+                let cell = match state {
+                    1 => wire_board::Cell::Conductor,
+                    2 => wire_board::Cell::Head,
+                    3 => wire_board::Cell::Tail,
+                    _ => wire_board::Cell::Empty,
+                };
+                wire.set(x, y, cell)
+            }
+            Boards::LtlBoard(ltl) => ltl.set(x, y, state),
+            Boards::LatticeBoard(lattice) => lattice.set(x, y, state != 0),
+            Boards::MargolusBoard(margolus) => margolus.set(x, y, state != 0),
+        }
+    }
+
+    /// Advances the cell at (x, y) to the next of its possible states
+    pub fn cycle_cell(&mut self, x: usize, y: usize) {
+        match self {
+            Boards::GolBoard(gol) => gol.set(x, y, !gol.get(x, y)),
+            Boards::AntBoard(ant) => ant.set(x, y, !ant.get(x, y)),
+            Boards::WireBoard(wire) => wire.set(x, y, wire.get(x, y).cycle()),
+            Boards::LtlBoard(ltl) => ltl.set(x, y, ltl.get(x, y) + 1),
+            Boards::LatticeBoard(lattice) => lattice.set(x, y, !lattice.get(x, y)),
+            Boards::MargolusBoard(margolus) => margolus.set(x, y, !margolus.get(x, y)),
+        }
+    }
+
+    /// Paints the cell at (x, y) with the board's drawing state: alive cells, or conductors in
+    /// Wireworld
+    pub fn draw_cell(&mut self, x: usize, y: usize) {
+        match self {
+            Boards::GolBoard(gol) => gol.set(x, y, true),
+            Boards::AntBoard(ant) => ant.set(x, y, true),
+            Boards::WireBoard(wire) => wire.set(x, y, wire_board::Cell::Conductor),
+            Boards::LtlBoard(ltl) => ltl.set(x, y, 1),
+            Boards::LatticeBoard(lattice) => lattice.set(x, y, true),
+            Boards::MargolusBoard(margolus) => margolus.set(x, y, true),
+        }
+    }
+
+    /// Returns the cell at (x, y) to the background state
+    pub fn clear_cell(&mut self, x: usize, y: usize) {
+        match self {
+            Boards::GolBoard(gol) => gol.set(x, y, false),
+            Boards::AntBoard(ant) => ant.set(x, y, false),
+            Boards::WireBoard(wire) => wire.set(x, y, wire_board::Cell::Empty),
+            Boards::LtlBoard(ltl) => ltl.set(x, y, 0),
+            Boards::LatticeBoard(lattice) => lattice.set(x, y, false),
+            Boards::MargolusBoard(margolus) => margolus.set(x, y, false),
+        }
+    }
+
+    /// Returns every cell to the background state
+    pub fn clear(&mut self) {
+        let (width, height) = self.size();
+        for y in 0..height {
+            for x in 0..width {
+                self.clear_cell(x, y);
+            }
+        }
+    }
+
+    /// Writes boards that `gol file` can load as lines of `0` and `1`, after the comments
+    pub fn save_to_file(&self, filename: &str, comments: &[String]) -> std::io::Result<()> {
+        let alive: Box<dyn Fn(usize, usize) -> bool> = match self {
+            Boards::GolBoard(gol) => Box::new(|x, y| gol.get(x, y)),
+            Boards::LatticeBoard(lattice) => Box::new(|x, y| lattice.get(x, y)),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("{} boards can't be saved", self.name()),
+                ))
+            }
+        };
+
+        let (width, height) = self.size();
+        let mut contents = String::new();
+        for comment in comments {
+            contents.push_str(&format!("# {}\n", comment));
+        }
+        for y in 0..height {
+            for x in 0..width {
+                contents.push(if alive(x, y) { '1' } else { '0' });
+            }
+            contents.push('\n');
+        }
+
+        std::fs::write(filename, contents)
+    }
 }
@@ -0,0 +1,226 @@
+use std::path::Path;
+
+use crate::GOLError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Empty,
+    Conductor,
+    Head,
+    Tail,
+}
+
+impl Cell {
+    pub fn from_char(c: char) -> Option<Cell> {
+        match c {
+            ' ' | '.' => Some(Cell::Empty),
+            '#' => Some(Cell::Conductor),
+            '@' => Some(Cell::Head),
+            '~' => Some(Cell::Tail),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Empty => ' ',
+            Cell::Conductor => '#',
+            Cell::Head => '@',
+            Cell::Tail => '~',
+        }
+    }
+
+    /// The next state in the editing cycle: empty -> conductor -> head -> tail -> empty
+    pub fn cycle(self) -> Cell {
+        match self {
+            Cell::Empty => Cell::Conductor,
+            Cell::Conductor => Cell::Head,
+            Cell::Head => Cell::Tail,
+            Cell::Tail => Cell::Empty,
+        }
+    }
+}
+
+type StateArray = Vec<Vec<Cell>>;
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    state: StateArray,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Board {
+    pub fn empty_state(width: usize, height: usize) -> Board {
+        let state = vec![vec![Cell::Empty; width]; height];
+
+        Board {
+            state,
+            width,
+            height,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.state[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.state[y][x] = cell;
+    }
+
+    pub fn render(&self) -> String {
+        let mut string = String::new();
+        let divider = "-".repeat(self.width + 2);
+        string.push_str(&divider);
+        string.push('\n');
+        for y in &self.state {
+            string.push('|');
+            for x in y {
+                string.push(x.to_char());
+            }
+            string.push('|');
+            string.push('\n');
+        }
+        string.push_str(&divider);
+
+        string
+    }
+
+    pub fn next_state(&self) -> Board {
+        let mut new_state = self.state.clone();
+        for (y, line) in self.state.iter().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                new_state[y][x] = match cell {
+                    Cell::Empty => Cell::Empty,
+                    Cell::Head => Cell::Tail,
+                    Cell::Tail => Cell::Conductor,
+                    Cell::Conductor => match self.calculate_num_heads(x, y) {
+                        1 | 2 => Cell::Head,
+                        _ => Cell::Conductor,
+                    },
+                };
+            }
+        }
+
+        Board {
+            state: new_state,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn calculate_num_heads(&self, x: usize, y: usize) -> u8 {
+        let mut counter = 0;
+
+        for ny in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                if (nx, ny) != (x, y) && self.state[ny][nx] == Cell::Head {
+                    counter += 1;
+                }
+            }
+        }
+
+        counter
+    }
+
+    /// Loads a circuit where `#` is a conductor, `@` an electron head, `~` an electron tail and
+    /// `.` or a space is empty. Short lines are padded with empty cells.
+    pub fn load_from_file(filename: impl AsRef<Path>) -> Result<Board, GOLError> {
+        let path = filename.as_ref();
+        let file = std::fs::read_to_string(path).map_err(|err| GOLError::io(path, err))?;
+
+        parse(&file).map_err(|(line, column, message)| GOLError::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message,
+        })
+    }
+}
+
+/// The circuit in a file's text, or the line and column, counted from 1, of what's wrong with it
+fn parse(text: &str) -> Result<Board, (usize, usize, String)> {
+    let width = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    if width == 0 {
+        return Err((1, 1, "the circuit is empty".to_string()));
+    }
+
+    let mut state = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let mut row = Vec::with_capacity(width);
+        for (column, c) in line.chars().enumerate() {
+            match Cell::from_char(c) {
+                Some(cell) => row.push(cell),
+                None => {
+                    return Err((
+                        number + 1,
+                        column + 1,
+                        format!("unknown wireworld cell '{}'", c),
+                    ))
+                }
+            }
+        }
+        row.resize(width, Cell::Empty);
+        state.push(row);
+    }
+
+    Ok(Board {
+        height: state.len(),
+        width,
+        state,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cell::*;
+
+    #[test]
+    pub fn test_electron_moves() {
+        let board = super::Board {
+            state: vec![vec![Tail, Head, Conductor, Conductor]],
+            width: 4,
+            height: 1,
+        };
+
+        let next_state = board.next_state();
+
+        assert_eq!(
+            vec![vec![Conductor, Tail, Head, Conductor]],
+            next_state.state
+        );
+    }
+
+    #[test]
+    pub fn test_crowded_conductor_stays() {
+        let board = super::Board {
+            state: vec![vec![Head, Head, Head], vec![Empty, Conductor, Empty]],
+            width: 3,
+            height: 2,
+        };
+
+        let next_state = board.next_state();
+
+        assert_eq!(Conductor, next_state.get(1, 1));
+    }
+
+    #[test]
+    pub fn test_parse() {
+        let board = super::parse("#@~\n .#\n#").unwrap();
+        assert_eq!((3, 3), (board.width, board.height));
+        assert_eq!(vec![Conductor, Empty, Empty], board.state[2]);
+
+        let error = |text| {
+            super::parse(text)
+                .err()
+                .map(|(line, column, _)| (line, column))
+        };
+        assert_eq!(Some((2, 3)), error("###\n##x\n"));
+        assert_eq!(Some((1, 1)), error("\n\n"));
+    }
+}
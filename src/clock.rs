@@ -0,0 +1,124 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The longest time between generations the speed can be slowed to
+const SLOWEST: Duration = Duration::from_secs(10);
+
+/// Keeps generations coming at a steady rate, however often the board is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    /// The time between generations, zero to run as many as there is time for
+    pub interval: Duration,
+    /// When the next generation is due
+    next: Instant,
+}
+
+impl Clock {
+    pub fn new(interval: Duration, now: Instant) -> Clock {
+        Clock {
+            interval,
+            next: now + interval,
+        }
+    }
+
+    /// How many generations are due by `now`, counting them as run. Generations that aren't run
+    /// in time are dropped rather than caught up on later.
+    pub fn due(&mut self, now: Instant) -> u64 {
+        if self.interval.is_zero() {
+            return u64::MAX;
+        }
+        if now < self.next {
+            return 0;
+        }
+
+        let due = ((now - self.next).as_nanos() / self.interval.as_nanos()) as u64 + 1;
+        self.next += self.interval * due.min(u32::MAX as u64) as u32;
+        if self.next <= now {
+            self.next = now + self.interval;
+        }
+
+        due
+    }
+
+    /// When the next generation is due
+    pub fn next(&self) -> Instant {
+        self.next
+    }
+
+    /// Starts counting again from `now`, so that a pause doesn't leave generations due
+    pub fn reset(&mut self, now: Instant) {
+        self.next = now + self.interval;
+    }
+
+    /// Halves the time between generations, down to none at all
+    pub fn faster(&mut self) {
+        self.interval /= 2;
+        if self.interval < Duration::from_millis(1) {
+            self.interval = Duration::ZERO;
+        }
+    }
+
+    /// Doubles the time between generations
+    pub fn slower(&mut self) {
+        self.interval = (self.interval * 2)
+            .max(Duration::from_millis(1))
+            .min(SLOWEST);
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.interval.is_zero() {
+            true => write!(f, "max speed"),
+            false => write!(f, "{:.1} gen/s", 1.0 / self.interval.as_secs_f64()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Clock;
+
+    #[test]
+    pub fn test_due() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut clock = Clock::new(ms(10), start);
+
+        assert_eq!(0, clock.due(start + ms(5)));
+        assert_eq!(1, clock.due(start + ms(10)));
+        assert_eq!(0, clock.due(start + ms(15)));
+        // Several generations are due in one frame
+        assert_eq!(4, clock.due(start + ms(55)));
+        assert_eq!(start + ms(60), clock.next());
+
+        clock.reset(start + ms(1000));
+        assert_eq!(0, clock.due(start + ms(1005)));
+
+        clock.interval = Duration::ZERO;
+        assert_eq!(u64::MAX, clock.due(start));
+    }
+
+    #[test]
+    pub fn test_speed() {
+        let mut clock = Clock::new(Duration::from_millis(4), Instant::now());
+        assert_eq!("250.0 gen/s", clock.to_string());
+
+        clock.faster();
+        clock.faster();
+        assert_eq!(Duration::from_millis(1), clock.interval);
+        clock.faster();
+        assert_eq!("max speed", clock.to_string());
+
+        clock.slower();
+        assert_eq!(Duration::from_millis(1), clock.interval);
+        for _ in 0..20 {
+            clock.slower();
+        }
+        assert_eq!(Duration::from_secs(10), clock.interval);
+    }
+}
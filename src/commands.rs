@@ -1,57 +1,418 @@
-use clap::Parser;
+use std::path::{Path, PathBuf};
 
-use crate::args::{Cli, SubCommands};
-use crate::boards::gol_board;
-use crate::boards::{ant_board, Boards};
+use clap::{CommandFactory, FromArgMatches};
+
+use crate::analysis::apgcode;
+use crate::analysis::census::{Census, CensusOptions};
+use crate::analysis::search::{self, SearchOptions};
+use crate::args::{
+    Cli, GOLMethod, Grid, ImageArgs, ImportArgs, MargolusRule, MetricsArgs, PlaceArgs, SoupArgs,
+    SubCommands,
+};
+use crate::boards::{ant_board, lattice_board, ltl_board, margolus_board, wire_board, Boards};
+use crate::boards::{gol_board, hensel};
+use crate::config::Config;
+use crate::export::{self, ImageOptions};
 use crate::game::Game;
+use crate::import::{self, ImportOptions};
+use crate::library::Library;
+use crate::scene::Scene;
+use crate::soup::Soup;
+use crate::ui::Ui;
 use crate::GOLError;
 
+/// Empty cells around a pattern loaded from an apgcode
+const APGCODE_MARGIN: usize = 10;
+
 pub fn run() -> Result<(), GOLError> {
-    let args = Cli::parse();
+    let loaded =
+        Config::load().and_then(|config| Ok((config.apply_defaults(Cli::command())?, config)));
+    let (matches, config) = match loaded {
+        Ok((command, config)) => (command.get_matches(), config),
+        Err(err) => {
+            // Help and mistakes on the command line don't need the config
+            Cli::command().get_matches();
+            return Err(err);
+        }
+    };
+    let args = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     match args.command {
-        SubCommands::Random(args) => random(args),
-        SubCommands::File(args) => from_file(args),
-        SubCommands::Ant(args) => ant(args),
+        SubCommands::Random(args) => random(args, &config),
+        SubCommands::File(args) => from_file(args, &config),
+        SubCommands::Ant(args) => ant(args, &config),
+        SubCommands::Wire(args) => wire(args, &config),
+        SubCommands::Ltl(args) => ltl(args, &config),
+        SubCommands::Margolus(args) => margolus(args, &config),
+        SubCommands::Run(args) => run_headless(args),
+        SubCommands::Record(args) => record(args),
+        SubCommands::Search(args) => search(args),
     }
 }
 
-fn random(args: crate::args::Random) -> Result<(), GOLError> {
-    let board = gol_board::Board::random_state(args.width, args.height, args.method);
-
-    let mut game = Game {
-        board: Boards::GolBoard(board),
+fn lattice_rule(grid: Grid, rule: &Option<String>) -> Result<lattice_board::Rule, GOLError> {
+    let rule = match rule {
+        Some(rule) => rule.parse()?,
+        None if grid == Grid::Hex => lattice_board::Rule::default_hex(),
+        None => lattice_board::Rule::default_tri(),
     };
 
-    crate::ui::start_ui(&mut game, args.sleep_time)
+    if rule.neighborhood.is_hex() != (grid == Grid::Hex) {
+        return Err(GOLError::InvalidRule(rule.to_string()));
+    }
+
+    Ok(rule)
+}
+
+/// Loads a board from a text file, an image, or an apgcode when there is no such file
+fn load_board(path: &Path, import: &ImportOptions) -> Result<gol_board::Board, GOLError> {
+    if !path.exists() {
+        if let Some(code) = path.to_str().filter(|code| code.starts_with('x')) {
+            return Ok(apgcode::decode(code)?.to_board(APGCODE_MARGIN, GOLMethod::Normal));
+        }
+    }
+
+    if import::is_image(path) {
+        return match import::load_image(path, import) {
+            Ok(pattern) => Ok(pattern.to_board(0, GOLMethod::Normal)),
+            Err(err) => Err(GOLError::io(path, err)),
+        };
+    }
+
+    gol_board::Board::load_from_file(path, import.pad)
 }
 
-fn from_file(args: crate::args::File) -> Result<(), GOLError> {
-    let board = match gol_board::Board::load_from_file(args.filepath.to_str().unwrap()) {
-        Ok(brd) => brd,
-        Err(_) => return Err(GOLError::InvalidFile),
+/// Loads the board to start from, placed onto the board given with `--onto` if there is one
+fn load_start(path: &Path, args: &ImportArgs) -> Result<gol_board::Board, GOLError> {
+    let import = ImportOptions {
+        threshold: args.threshold,
+        scale: args.scale,
+        invert: args.invert,
+        pad: args.pad,
     };
+    let board = load_board(path, &import)?;
 
-    let mut game = Game {
-        board: Boards::GolBoard(board),
+    let Some(onto) = &args.onto else {
+        return Ok(board);
     };
 
-    crate::ui::start_ui(&mut game, args.sleep_time)
+    let mut base = load_board(onto, &import)?;
+    let (left, top) = args.offset;
+    for y in 0..board.height.min(base.height.saturating_sub(top)) {
+        for x in 0..board.width.min(base.width.saturating_sub(left)) {
+            if board.get(x, y) {
+                base.set(left + x, top + y, true);
+            }
+        }
+    }
+
+    Ok(base)
+}
+
+/// Streams the game's metrics to a file, if one was given
+fn record_metrics(game: &mut Game, args: &MetricsArgs) -> Result<(), GOLError> {
+    if let Some(path) = &args.path {
+        game.record_metrics(path)
+            .map_err(|err| GOLError::io(path, err))?;
+    }
+
+    Ok(())
+}
+
+/// Stamps the patterns from the scene file and then those given with `--place` onto the board
+fn place(board: &mut Boards, args: &PlaceArgs) -> Result<(), GOLError> {
+    if args.scene.is_none() && args.place.is_empty() {
+        return Ok(());
+    }
+
+    let (library, _) = Library::load();
+
+    if let Some(path) = &args.scene {
+        Scene::load_from_file(path)?.apply(board, &library)?;
+    }
+
+    Scene {
+        placements: args.place.clone(),
+        ..Default::default()
+    }
+    .apply(board, &library)
+}
+
+/// Starts a game on an empty board filled with a soup made from the arguments, with any patterns
+/// placed on top
+fn new_soup(mut board: Boards, args: &SoupArgs, placements: &PlaceArgs) -> Result<Game, GOLError> {
+    let soup = Soup::new(args.seed, args.density, args.region);
+    soup.fill(&mut board);
+    place(&mut board, placements)?;
+
+    let mut game = Game::new(board);
+    game.soup = Some(soup);
+
+    Ok(game)
+}
+
+fn random(args: crate::args::Random, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
+    if args.grid != Grid::Square {
+        let rule = lattice_rule(args.grid, &args.rule)?;
+        let board = lattice_board::Board::dead_state(args.width, args.height, rule);
+
+        let mut game = new_soup(Boards::LatticeBoard(board), &args.soup, &args.place)?;
+        record_metrics(&mut game, &args.metrics)?;
+        game.fast_forward(args.start.generation);
+
+        return crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps);
+    }
+
+    let mut board = gol_board::Board::dead_state(args.width, args.height, args.method);
+
+    if let Some(rule) = &args.rule {
+        board = board.with_rule(hensel::preset(rule).parse()?);
+    }
+
+    let mut game = new_soup(Boards::GolBoard(board), &args.soup, &args.place)?;
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start.generation);
+
+    crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps)
+}
+
+fn from_file(args: crate::args::File, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
+    let mut board = load_start(&args.filepath, &args.import)?;
+
+    if args.grid != Grid::Square {
+        let rule = lattice_rule(args.grid, &args.rule)?;
+        let mut lattice = lattice_board::Board::dead_state(board.width, board.height, rule);
+        for y in 0..board.height {
+            for x in 0..board.width {
+                lattice.set(x, y, board.get(x, y));
+            }
+        }
+
+        let mut lattice = Boards::LatticeBoard(lattice);
+        place(&mut lattice, &args.place)?;
+
+        let mut game = Game::new(lattice);
+        record_metrics(&mut game, &args.metrics)?;
+        game.fast_forward(args.start.generation);
+
+        return crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps);
+    }
+
+    board.method = args.method;
+
+    if let Some(rule) = &args.rule {
+        board = board.with_rule(hensel::preset(rule).parse()?);
+    }
+
+    let mut board = Boards::GolBoard(board);
+    place(&mut board, &args.place)?;
+
+    let mut game = Game::new(board);
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start.generation);
+
+    crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps)
 }
 
-fn ant(args: crate::args::Ant) -> Result<(), GOLError> {
+fn ant(args: crate::args::Ant, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
     let board = ant_board::Board::create_board(args.width, args.height);
 
-    let mut game = Game {
-        board: Boards::AntBoard(board),
+    let mut game = Game::new(Boards::AntBoard(board));
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start);
+
+    crate::ui::start_ui(&mut game, &ui, args.interval, args.draw.fps)
+}
+
+fn wire(args: crate::args::Wire, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
+    let board = wire_board::Board::load_from_file(&args.filepath)?;
+
+    let mut game = Game::new(Boards::WireBoard(board));
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start.generation);
+
+    crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps)
+}
+
+fn ltl(args: crate::args::Ltl, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
+    let rule = ltl_board::preset(&args.rule).parse()?;
+    let board = ltl_board::Board::dead_state(args.width, args.height, rule);
+
+    let mut game = new_soup(Boards::LtlBoard(board), &args.soup, &PlaceArgs::default())?;
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start.generation);
+
+    crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps)
+}
+
+fn margolus(args: crate::args::Margolus, config: &Config) -> Result<(), GOLError> {
+    let ui = Ui::new(config)?;
+    let rule = match args.rule {
+        MargolusRule::Critters => margolus_board::Rule::critters(),
+        MargolusRule::Tron => margolus_board::Rule::tron(),
+        MargolusRule::Bbm => margolus_board::Rule::billiard_ball(),
+        MargolusRule::Sand => margolus_board::Rule::sand(),
     };
+    let board = margolus_board::Board::dead_state(args.width, args.height, rule);
+
+    let mut game = new_soup(
+        Boards::MargolusBoard(board),
+        &args.soup,
+        &PlaceArgs::default(),
+    )?;
+    record_metrics(&mut game, &args.metrics)?;
+    game.fast_forward(args.start.generation);
 
-    for _ in 0..args.start {
-        match &mut game.board {
-            Boards::AntBoard(x) => *x = x.update(),
-            _ => return Err(GOLError::UnknownError),
+    crate::ui::start_ui(&mut game, &ui, args.sleep_time, args.draw.fps)
+}
+
+/// The file a snapshot of a generation is written to: `snapshot.png` becomes
+/// `snapshot-gen100.png`
+fn snapshot_path(path: &Path, generation: u64) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("snapshot");
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png");
+
+    path.with_file_name(format!("{}-gen{}.{}", stem, generation, extension))
+}
+
+fn run_headless(args: crate::args::Run) -> Result<(), GOLError> {
+    let mut board = load_start(&args.filepath, &args.import)?;
+
+    board.method = args.method;
+
+    if let Some(rule) = &args.rule {
+        board = board.with_rule(hensel::preset(rule).parse()?);
+    }
+
+    let mut board = Boards::GolBoard(board);
+    place(&mut board, &args.place)?;
+
+    let mut game = Game::new(board);
+    record_metrics(&mut game, &args.metrics)?;
+
+    let options = image_options(&args.image);
+    crate::headless::run(&mut game, args.generations, args.until_stable, |game| {
+        if args.snapshot_at.contains(&game.generation) {
+            let path = snapshot_path(&args.snapshot_out, game.generation);
+            export::snapshot(&game.board, &path, &options)
+                .map_err(|err| GOLError::io(&path, err))?;
         }
+
+        Ok(())
+    })?;
+
+    println!("{}", game.board.render());
+    println!("{}", crate::headless::summary(&game));
+
+    if let Some(message) = &game.message {
+        println!("{}", message);
+    }
+
+    if args.census {
+        if let Boards::GolBoard(board) = &game.board {
+            let options = CensusOptions {
+                connectivity: args.connectivity,
+                distance: args.distance,
+            };
+            print!("{}", Census::take(board, options));
+        }
+    }
+
+    Ok(())
+}
+
+fn image_options(args: &ImageArgs) -> ImageOptions {
+    ImageOptions {
+        cell_size: args.cell_size,
+        live: args.live_color,
+        dead: args.dead_color,
+        ant: args.ant_color,
+        crop: args.crop,
+        grid: args.grid_color,
+    }
+}
+
+fn record(args: crate::args::Record) -> Result<(), GOLError> {
+    let mut board = match &args.pattern {
+        Some(pattern) if !args.ant => {
+            let mut board = load_board(pattern, &ImportOptions::default())?;
+            board.method = args.method;
+
+            if let Some(rule) = &args.rule {
+                board = board.with_rule(hensel::preset(rule).parse()?);
+            }
+
+            Boards::GolBoard(board)
+        }
+        _ => Boards::AntBoard(ant_board::Board::create_board(args.width, args.height)),
+    };
+
+    let options = image_options(&args.image);
+
+    let out = std::fs::File::create(&args.out).map_err(|err| GOLError::io(&args.out, err))?;
+    export::gif::record(
+        &mut board,
+        args.generations,
+        std::io::BufWriter::new(out),
+        &options,
+        args.delay,
+    )
+    .map_err(|err| GOLError::io(&args.out, err))?;
+
+    println!(
+        "recorded {} generations to {}",
+        args.generations + 1,
+        args.out.display()
+    );
+
+    Ok(())
+}
+
+fn search(args: crate::args::Search) -> Result<(), GOLError> {
+    let options = SearchOptions {
+        size: args.size,
+        density: args.density,
+        padding: args.padding,
+        generations: args.generations,
+    };
+    let first_seed = args.seed.unwrap_or_else(rand::random);
+
+    let results = search::search(first_seed, args.soups, &options);
+
+    println!(
+        "searched {} soups of {}x{} at density {} from seed {}",
+        args.soups, args.size, args.size, args.density, first_seed
+    );
+
+    println!("longest lived:");
+    for result in results.iter().take(args.top) {
+        match result.lifespan {
+            Some(lifespan) => println!(
+                "  seed {}: lifespan {}, population {}",
+                result.seed, lifespan, result.population
+            ),
+            None => println!(
+                "  seed {}: still running after {} generations, population {}",
+                result.seed, args.generations, result.population
+            ),
+        }
+    }
+
+    println!("rare objects:");
+    for result in results.iter().filter(|result| !result.rare.is_empty()) {
+        println!("  seed {}: {}", result.seed, result.rare.join(", "));
     }
 
-    crate::ui::start_ui(&mut game, args.interval)
+    Ok(())
 }
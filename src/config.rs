@@ -0,0 +1,365 @@
+use std::{collections::HashMap, error::Error, path::PathBuf};
+
+use clap::{Arg, Command};
+use serde::Deserialize;
+use tui::style::Color;
+
+use crate::{
+    keymap::{Action, Keymap, Keys},
+    GOLError,
+};
+
+/// A color of the UI, either a terminal color such as `lightcyan` or `darkgray`, or a hex color
+/// such as `#ff8800`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ThemeColor(pub Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let color = match s.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => {
+                let crate::export::Color(r, g, b) = s.parse()?;
+                Color::Rgb(r, g, b)
+            }
+        };
+
+        Ok(ThemeColor(color))
+    }
+}
+
+/// How the UI looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// The name of the board at the top
+    pub title: ThemeColor,
+    /// The board itself
+    pub board: ThemeColor,
+    /// The status line at the bottom
+    pub status: ThemeColor,
+    /// The background of selected cells
+    pub selection: ThemeColor,
+    /// How a live cell is drawn
+    pub alive: char,
+    /// How a dead cell is drawn
+    pub dead: char,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            title: ThemeColor(Color::LightCyan),
+            board: ThemeColor(Color::Reset),
+            status: ThemeColor(Color::Gray),
+            selection: ThemeColor(Color::DarkGray),
+            alive: '#',
+            dead: ' ',
+        }
+    }
+}
+
+/// The user's settings, read from `config.toml` in the config directory
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Defaults for command line options, named like the options such as `sleep-time = 100`.
+    /// Tables named after a command, such as `[defaults.ltl]`, only apply to that command.
+    pub defaults: toml::Table,
+    pub theme: Theme,
+    /// Keys for actions, such as `pause = ["p", "space"]`
+    pub keys: HashMap<Action, Keys>,
+}
+
+impl Config {
+    /// The config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gol").join("config.toml"))
+    }
+
+    /// The config file, or the defaults if there is none
+    pub fn load() -> Result<Config, GOLError> {
+        match Config::path() {
+            Some(path) if path.exists() => {
+                let config =
+                    std::fs::read_to_string(&path).map_err(|err| GOLError::io(&path, err))?;
+                Config::from_toml(&config)
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn from_toml(config: &str) -> Result<Config, GOLError> {
+        toml::from_str(config).map_err(|err| GOLError::InvalidConfig(err.to_string()))
+    }
+
+    /// The default keys with those of the config file
+    pub fn keymap(&self) -> Result<Keymap, GOLError> {
+        Keymap::from_keys(&self.keys)
+    }
+
+    /// Makes the options of the `[defaults]` table the defaults of the commands that have them,
+    /// so that options given on the command line still win. Outside of a command's own table only
+    /// named options and the width and height of the grid are changed, as other arguments such
+    /// as the rule of `ltl` mean something else in each command.
+    pub fn apply_defaults(&self, mut command: Command) -> Result<Command, GOLError> {
+        command = pin_order(command);
+        let names: Vec<String> = command
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+
+        for (option, value) in &self.defaults {
+            if value.is_table() {
+                continue;
+            }
+
+            let value = value_string(option, value)?;
+            let id = option.replace('-', "_");
+            let mut found = false;
+            for name in &names {
+                let has_option = command
+                    .find_subcommand(name)
+                    .and_then(|subcommand| {
+                        subcommand
+                            .get_arguments()
+                            .find(|arg| arg.get_id() == id.as_str())
+                    })
+                    .is_some_and(|arg| !arg.is_positional() || id == "width" || id == "height");
+
+                if has_option {
+                    found = true;
+                    command = set_default(command, name, &id, option, &value)?;
+                }
+            }
+
+            if !found {
+                return Err(GOLError::InvalidConfig(format!(
+                    "unknown option {}",
+                    option
+                )));
+            }
+        }
+
+        for (name, table) in &self.defaults {
+            let Some(table) = table.as_table() else {
+                continue;
+            };
+            let Some(subcommand) = command.find_subcommand(name) else {
+                return Err(GOLError::InvalidConfig(format!("unknown command {}", name)));
+            };
+
+            let mut options = Vec::new();
+            for (option, value) in table {
+                let id = option.replace('-', "_");
+                if !subcommand
+                    .get_arguments()
+                    .any(|arg| arg.get_id() == id.as_str())
+                {
+                    return Err(GOLError::InvalidConfig(format!(
+                        "unknown option {} of {}",
+                        option, name
+                    )));
+                }
+                options.push((id, option, value_string(option, value)?));
+            }
+
+            for (id, option, value) in options {
+                command = set_default(command, name, &id, option, &value)?;
+            }
+        }
+
+        Ok(command)
+    }
+}
+
+/// Fixes the order of the commands and their arguments as declared, as changing one moves it
+/// to the end
+fn pin_order(mut command: Command) -> Command {
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+
+    for (order, name) in names.iter().enumerate() {
+        command = command.mut_subcommand(name, |mut subcommand| {
+            let ids: Vec<(String, bool)> = subcommand
+                .get_arguments()
+                .map(|arg| (arg.get_id().to_string(), arg.is_positional()))
+                .collect();
+
+            let mut index = 0;
+            for (order, (id, positional)) in ids.into_iter().enumerate() {
+                subcommand = subcommand.mut_arg(id, |arg| match positional {
+                    true => {
+                        index += 1;
+                        arg.display_order(order).index(index)
+                    }
+                    false => arg.display_order(order),
+                });
+            }
+
+            subcommand.display_order(order)
+        });
+    }
+
+    command
+}
+
+/// Makes `value` the default of an argument, if it is one the argument would accept on the
+/// command line
+fn set_default(
+    command: Command,
+    subcommand: &str,
+    id: &str,
+    option: &str,
+    value: &str,
+) -> Result<Command, GOLError> {
+    if let Some(parent) = command.find_subcommand(subcommand) {
+        if let Some(arg) = parent.get_arguments().find(|arg| arg.get_id() == id) {
+            // clap only checks defaults in debug builds, and then by panicking
+            let parsed = Command::new(subcommand.to_string())
+                .no_binary_name(true)
+                .arg(
+                    Arg::new("value")
+                        .value_parser(arg.get_value_parser().clone())
+                        .allow_hyphen_values(true),
+                )
+                .try_get_matches_from([value]);
+            if let Err(err) = parsed {
+                return Err(GOLError::InvalidConfig(match err.source() {
+                    Some(source) => format!("invalid value {} for {}: {}", value, option, source),
+                    None => format!("invalid value {} for {}", value, option),
+                }));
+            }
+        }
+    }
+
+    let value = value.to_string();
+    Ok(command.mut_subcommand(subcommand, |subcommand| {
+        subcommand.mut_arg(id, |arg| arg.default_value(value))
+    }))
+}
+
+/// A default as it would be typed on the command line
+fn value_string(option: &str, value: &toml::Value) -> Result<String, GOLError> {
+    match value {
+        toml::Value::String(value) => Ok(value.clone()),
+        toml::Value::Integer(value) => Ok(value.to_string()),
+        toml::Value::Float(value) => Ok(value.to_string()),
+        toml::Value::Boolean(value) => Ok(value.to_string()),
+        _ => Err(GOLError::InvalidConfig(format!(
+            "{} should be a string, number or boolean",
+            option
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+    use tui::style::Color;
+
+    use super::{Config, ThemeColor};
+    use crate::args::{Cli, MargolusRule, SubCommands};
+
+    fn parse(config: &Config, args: &[&str]) -> SubCommands {
+        let command = config.apply_defaults(Cli::command()).unwrap();
+        let matches = command.try_get_matches_from(args).unwrap();
+        Cli::from_arg_matches(&matches).unwrap().command
+    }
+
+    #[test]
+    pub fn test_apply_defaults() {
+        let config = Config::from_toml(
+            "[defaults]\nwidth = 30\nsleep-time = 50\nrule = \"B36/S23\"\ngrid = \"hex\"\n\
+             [defaults.ltl]\nrule = \"majority\"\n",
+        )
+        .unwrap();
+
+        let SubCommands::Random(random) = parse(&config, &["gol", "random"]) else {
+            unreachable!()
+        };
+        assert_eq!(30, random.width);
+        assert_eq!(50, random.sleep_time);
+        assert_eq!(Some("B36/S23".to_string()), random.rule);
+
+        // The command line wins
+        let SubCommands::Random(random) =
+            parse(&config, &["gol", "random", "10", "-s", "5", "-g", "square"])
+        else {
+            unreachable!()
+        };
+        assert_eq!(10, random.width);
+        assert_eq!(5, random.sleep_time);
+
+        let SubCommands::Ltl(ltl) = parse(&config, &["gol", "ltl"]) else {
+            unreachable!()
+        };
+        assert_eq!("majority", ltl.rule);
+        assert_eq!(30, ltl.width);
+
+        // Positional rules only change in a command's own table
+        let SubCommands::Margolus(margolus) = parse(&config, &["gol", "margolus"]) else {
+            unreachable!()
+        };
+        assert_eq!(MargolusRule::Critters, margolus.rule);
+
+        for config in [
+            "[defaults]\nspeed = 3\n",
+            "[defaults.fly]\nwidth = 3\n",
+            "[defaults.wire]\nwidth = 3\n",
+            "[defaults]\nwidth = [3]\n",
+            "[defaults]\nwidth = \"abc\"\n",
+            "[defaults]\nwidth = 0\n",
+            "[defaults]\ngrid = \"octagonal\"\n",
+            "[defaults.random]\nfps = 0\n",
+            "[defaults.record]\ncell-size = 0\n",
+            "[defaults.file]\nscale = 0\n",
+        ] {
+            let config = Config::from_toml(config).unwrap();
+            assert!(
+                config.apply_defaults(Cli::command()).is_err(),
+                "{:?}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_theme() {
+        let config = Config::from_toml(
+            "[theme]\ntitle = \"#ff8800\"\nstatus = \"dark-gray\"\nalive = \"O\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(ThemeColor(Color::Rgb(255, 136, 0)), config.theme.title);
+        assert_eq!(ThemeColor(Color::DarkGray), config.theme.status);
+        assert_eq!(ThemeColor(Color::Reset), config.theme.board);
+        assert_eq!('O', config.theme.alive);
+        assert_eq!(' ', config.theme.dead);
+
+        assert!(Config::from_toml("[theme]\ntitle = \"mauve\"\n").is_err());
+        assert!(Config::from_toml("[theme]\nalive = \"##\"\n").is_err());
+        assert!(Config::from_toml("[colours]\n").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{self, Write};
+
+use gif::{Encoder, Repeat};
+
+use crate::boards::Boards;
+
+use super::{rasterize, ImageOptions};
+
+fn to_io_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+/// Writes an animated GIF of the board's current generation and the `generations` after it, with
+/// `delay` milliseconds between frames. The board is left at the last generation.
+pub fn record(
+    board: &mut Boards,
+    generations: u64,
+    out: impl Write,
+    options: &ImageOptions,
+    delay: u64,
+) -> io::Result<()> {
+    let size = rasterize(board, options);
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{}x{} pixels is too large for a GIF",
+                size.width, size.height
+            ),
+        )
+    };
+    let width = u16::try_from(size.width).map_err(|_| too_large())?;
+    let height = u16::try_from(size.height).map_err(|_| too_large())?;
+
+    let palette: Vec<u8> = options
+        .palette()
+        .iter()
+        .flat_map(|color| [color.0, color.1, color.2])
+        .collect();
+
+    let mut encoder = Encoder::new(out, width, height, &palette).map_err(to_io_error)?;
+    encoder.set_repeat(Repeat::Infinite).map_err(to_io_error)?;
+
+    // GIF delays are in hundredths of a second
+    let delay = (delay / 10).min(u16::MAX as u64) as u16;
+
+    for generation in 0..=generations {
+        if generation > 0 {
+            board.update();
+        }
+
+        let frame = rasterize(board, options);
+        let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, frame.pixels, None);
+        gif_frame.delay = delay;
+        encoder.write_frame(&gif_frame).map_err(to_io_error)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::record;
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, Boards},
+        export::ImageOptions,
+    };
+
+    #[test]
+    pub fn test_record() {
+        let mut board = gol_board::Board::dead_state(5, 5, GOLMethod::Normal);
+        for x in 1..4 {
+            board.set(x, 2, true);
+        }
+        let mut board = Boards::GolBoard(board);
+
+        let mut out = Vec::new();
+        record(&mut board, 2, &mut out, &ImageOptions::default(), 100).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(&out[..]).unwrap();
+        assert_eq!((20, 20), (decoder.width(), decoder.height()));
+
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(10, frame.delay);
+            frames += 1;
+        }
+        assert_eq!(3, frames);
+    }
+}
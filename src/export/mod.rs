@@ -0,0 +1,200 @@
+pub mod gif;
+pub mod png;
+pub mod svg;
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{boards::Boards, soup::Region};
+
+/// Palette indices of the pixels in a `Frame`
+pub const DEAD: u8 = 0;
+pub const LIVE: u8 = 1;
+pub const ANT: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+/// Parses a hex color such as `#ff8800` or `ff8800`
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let invalid = || format!("{} is not a color such as #ff8800", s);
+
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+
+        Ok(Color(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// How boards are drawn as images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    /// Width and height of each cell in pixels
+    pub cell_size: usize,
+    pub live: Color,
+    pub dead: Color,
+    /// The color of Langton's ant
+    pub ant: Color,
+    /// The part of the board to draw, all of it if not given
+    pub crop: Option<Region>,
+    /// The color of the lines between cells, only drawn in SVGs
+    pub grid: Option<Color>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            cell_size: 4,
+            live: Color(255, 255, 255),
+            dead: Color(0, 0, 0),
+            ant: Color(255, 0, 0),
+            crop: None,
+            grid: None,
+        }
+    }
+}
+
+impl ImageOptions {
+    /// The colors of the `DEAD`, `LIVE` and `ANT` palette indices
+    pub fn palette(&self) -> [Color; 3] {
+        [self.dead, self.live, self.ant]
+    }
+}
+
+/// A board drawn as pixels, each the palette index of its color
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// Draws the cells of a board, or of the cropped part of it
+pub fn rasterize(board: &Boards, options: &ImageOptions) -> Frame {
+    let (board_width, board_height) = board.size();
+    let (columns, rows) = match options.crop {
+        Some(crop) => crop.bounds(board_width, board_height),
+        None => (0..board_width, 0..board_height),
+    };
+
+    let ant = match board {
+        Boards::AntBoard(ant) => {
+            let (x, y, _) = ant.ant();
+            Some((x, y))
+        }
+        _ => None,
+    };
+
+    let size = options.cell_size;
+    let width = columns.len() * size;
+    let height = rows.len() * size;
+    let mut pixels = vec![DEAD; width * height];
+
+    for (row, y) in rows.enumerate() {
+        for (column, x) in columns.clone().enumerate() {
+            let index = if ant == Some((x, y)) {
+                ANT
+            } else if board.alive(x, y) {
+                LIVE
+            } else {
+                continue;
+            };
+
+            for py in row * size..(row + 1) * size {
+                pixels[py * width + column * size..py * width + (column + 1) * size].fill(index);
+            }
+        }
+    }
+
+    Frame {
+        width,
+        height,
+        pixels,
+    }
+}
+
+/// Writes the board to a PNG or SVG file depending on its extension
+pub fn snapshot(board: &Boards, path: &Path, options: &ImageOptions) -> io::Result<()> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    if !matches!(extension, Some("png") | Some("svg")) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} doesn't end in .png or .svg", path.display()),
+        ));
+    }
+
+    let out = BufWriter::new(File::create(path)?);
+    match extension {
+        Some("svg") => svg::write(board, out, options),
+        _ => png::write(board, out, options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rasterize, Color, ImageOptions, ANT, DEAD, LIVE};
+    use crate::{
+        args::GOLMethod,
+        boards::{ant_board, gol_board, Boards},
+    };
+
+    #[test]
+    pub fn test_color() {
+        assert_eq!(Ok(Color(255, 136, 0)), "#ff8800".parse());
+        assert_eq!(Ok(Color(1, 2, 3)), "010203".parse());
+        assert!("#ff88".parse::<Color>().is_err());
+        assert_eq!("#ff8800", Color(255, 136, 0).to_string());
+    }
+
+    #[test]
+    pub fn test_rasterize() {
+        let mut board = gol_board::Board::dead_state(3, 2, GOLMethod::Normal);
+        board.set(1, 0, true);
+        let options = ImageOptions {
+            cell_size: 2,
+            ..Default::default()
+        };
+
+        let frame = rasterize(&Boards::GolBoard(board), &options);
+
+        assert_eq!((6, 4), (frame.width, frame.height));
+        assert_eq!(
+            vec![DEAD, DEAD, LIVE, LIVE, DEAD, DEAD],
+            frame.pixels[6..12].to_vec()
+        );
+        assert_eq!(
+            4,
+            frame.pixels.iter().filter(|pixel| **pixel == LIVE).count()
+        );
+
+        let ant = Boards::AntBoard(ant_board::Board::create_board(4, 4));
+        let cropped = ImageOptions {
+            cell_size: 1,
+            crop: "2x2+1+1".parse().ok(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![DEAD, DEAD, DEAD, ANT],
+            rasterize(&ant, &cropped).pixels
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+
+use crate::boards::Boards;
+
+use super::{rasterize, ImageOptions};
+
+fn to_io_error(err: png::EncodingError) -> io::Error {
+    match err {
+        png::EncodingError::IoError(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+/// Writes the board as a PNG with `cell_size` pixels per cell
+pub fn write(board: &Boards, out: impl Write, options: &ImageOptions) -> io::Result<()> {
+    let frame = rasterize(board, options);
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{}x{} pixels is too large for a PNG",
+                frame.width, frame.height
+            ),
+        )
+    };
+    let width = u32::try_from(frame.width).map_err(|_| too_large())?;
+    let height = u32::try_from(frame.height).map_err(|_| too_large())?;
+
+    let palette: Vec<u8> = options
+        .palette()
+        .iter()
+        .flat_map(|color| [color.0, color.1, color.2])
+        .collect();
+
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette);
+
+    let mut writer = encoder.write_header().map_err(to_io_error)?;
+    writer
+        .write_image_data(&frame.pixels)
+        .map_err(to_io_error)?;
+
+    writer.finish().map_err(to_io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write;
+    use crate::{
+        boards::{ant_board, Boards},
+        export::{Color, ImageOptions},
+    };
+
+    #[test]
+    pub fn test_write() {
+        let board = Boards::AntBoard(ant_board::Board::create_board(3, 3));
+        let options = ImageOptions {
+            cell_size: 1,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write(&board, &mut out, &options).unwrap();
+
+        let decoder = png::Decoder::new(&out[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+
+        assert_eq!((3, 3), (reader.info().width, reader.info().height));
+        let palette = reader.info().palette.as_ref().unwrap();
+        let center = pixels[4] as usize * 3;
+        assert_eq!(
+            options.ant,
+            Color(palette[center], palette[center + 1], palette[center + 2])
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use crate::boards::Boards;
+
+use super::{rasterize, ImageOptions, ANT, LIVE};
+
+/// Writes the board as an SVG with a square `cell_size` units wide for each live cell, and lines
+/// between the cells when the options have a grid color
+pub fn write(board: &Boards, mut out: impl Write, options: &ImageOptions) -> io::Result<()> {
+    // Draw one pixel per cell and scale it up here instead
+    let frame = rasterize(
+        board,
+        &ImageOptions {
+            cell_size: 1,
+            ..*options
+        },
+    );
+    let size = options.cell_size;
+    let (width, height) = (frame.width * size, frame.height * size);
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    )?;
+    writeln!(
+        out,
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        width, height, options.dead
+    )?;
+
+    for (i, pixel) in frame.pixels.iter().enumerate() {
+        let color = match *pixel {
+            LIVE => options.live,
+            ANT => options.ant,
+            _ => continue,
+        };
+
+        writeln!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            i % frame.width * size,
+            i / frame.width * size,
+            size,
+            size,
+            color
+        )?;
+    }
+
+    if let Some(grid) = options.grid {
+        let mut path = String::new();
+        for x in 0..=frame.width {
+            path.push_str(&format!("M{} 0V{}", x * size, height));
+        }
+        for y in 0..=frame.height {
+            path.push_str(&format!("M0 {}H{}", y * size, width));
+        }
+
+        writeln!(
+            out,
+            "<path d=\"{}\" stroke=\"{}\" stroke-width=\"1\" fill=\"none\"/>",
+            path, grid
+        )?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write;
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, Boards},
+        export::{Color, ImageOptions},
+    };
+
+    #[test]
+    pub fn test_write() {
+        let mut board = gol_board::Board::dead_state(2, 2, GOLMethod::Normal);
+        board.set(1, 1, true);
+        let options = ImageOptions {
+            cell_size: 10,
+            grid: Some(Color(128, 128, 128)),
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write(&Boards::GolBoard(board), &mut out, &options).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+
+        assert!(svg.contains("width=\"20\" height=\"20\""));
+        assert!(
+            svg.contains("<rect x=\"10\" y=\"10\" width=\"10\" height=\"10\" fill=\"#ffffff\"/>")
+        );
+        assert!(svg.contains("M0 0V20M10 0V20M20 0V20"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -1,7 +1,500 @@
-use crate::boards;
+use std::{path::Path, time::Instant};
 
+use crate::{
+    analysis::period::{Cycle, CycleDetector},
+    boards,
+    export::{self, ImageOptions},
+    history::{self, Edit, History},
+    library::{Entry, Library},
+    metrics::{self, Metrics, MetricsWriter},
+    pattern::{PasteMode, Pattern},
+    soup::Soup,
+};
 
+/// What is typed into the pattern library panel and which of the matching patterns is selected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Browser {
+    pub filter: String,
+    pub selected: usize,
+}
 
 pub struct Game {
-    pub board: boards::Boards
-}
\ No newline at end of file
+    pub board: boards::Boards,
+    pub generation: u64,
+    pub paused: bool,
+    pub reversed: bool,
+    pub drawing: bool,
+    pub cursor: (usize, usize),
+    /// The random settings the board was made from, if any
+    pub soup: Option<Soup>,
+    /// A message for the user about the last action
+    pub message: Option<String>,
+    /// What the clipboard holds, a library pattern's name or the size of a copied selection, and
+    /// the pattern in the orientation chosen
+    pub clipboard: (String, Pattern),
+    pub paste_mode: PasteMode,
+    /// The corner of the selection opposite the cursor, while selecting
+    pub selection: Option<(usize, usize)>,
+    pub library: Library,
+    /// The pattern library panel, when it is open
+    pub browser: Option<Browser>,
+    /// Whether the keys are being shown
+    pub help: bool,
+    /// The generation being typed in to go to
+    pub goto: Option<String>,
+    /// The generations being run without drawing, from and to
+    pub fast_forward: Option<(u64, u64)>,
+    /// Edits of the board that can be undone
+    history: History,
+    detector: CycleDetector,
+    /// Where metrics are streamed to, with the cells alive in the last generation recorded
+    metrics: Option<(MetricsWriter, Vec<bool>)>,
+}
+
+impl Game {
+    pub fn new(board: boards::Boards) -> Game {
+        let (width, height) = board.size();
+
+        let mut game = Game {
+            board,
+            generation: 0,
+            paused: false,
+            reversed: false,
+            drawing: false,
+            cursor: (width / 2, height / 2),
+            soup: None,
+            message: None,
+            clipboard: (String::new(), Pattern::empty(0, 0)),
+            paste_mode: PasteMode::Or,
+            selection: None,
+            library: Library::built_in(),
+            browser: None,
+            help: false,
+            goto: None,
+            fast_forward: None,
+            history: History::default(),
+            detector: CycleDetector::new(),
+            metrics: None,
+        };
+        game.observe();
+
+        let glider = game.library.get("glider").unwrap();
+        game.clipboard = (glider.name.clone(), glider.pattern.clone());
+
+        game
+    }
+
+    /// The cycle the board has settled into, only tracked for Game of Life boards
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.detector.cycle()
+    }
+
+    fn observe(&mut self) {
+        if let boards::Boards::GolBoard(board) = &self.board {
+            self.detector.observe(self.generation, board);
+        }
+    }
+
+    pub fn update(&mut self) {
+        if self.reversed {
+            self.rewind();
+            return;
+        }
+
+        self.board.update();
+        self.generation += 1;
+        self.observe();
+        self.record();
+    }
+
+    /// Steps back a generation on reversible boards, stopping at the first generation
+    pub fn rewind(&mut self) {
+        if self.generation > 0 && self.board.rewind() {
+            self.generation -= 1;
+            self.record();
+        }
+    }
+
+    /// Starts running to a later generation without drawing, returning whether the target is
+    /// ahead of the current generation
+    pub fn fast_forward(&mut self, target: u64) -> bool {
+        if target <= self.generation {
+            return false;
+        }
+
+        self.reversed = false;
+        self.fast_forward = Some((self.generation, target));
+        true
+    }
+
+    /// Runs the generations of a fast-forward until the deadline, stopping with a message once the
+    /// target is reached
+    pub fn run_fast_forward(&mut self, deadline: Instant) {
+        let Some((_, target)) = self.fast_forward else {
+            return;
+        };
+
+        while self.generation < target && Instant::now() < deadline {
+            self.update();
+        }
+
+        if self.generation >= target {
+            self.fast_forward = None;
+            self.message = Some(format!("reached generation {}", target));
+        }
+    }
+
+    /// Stops a fast-forward where it has got to
+    pub fn cancel_fast_forward(&mut self) {
+        if self.fast_forward.take().is_some() {
+            self.message = Some(format!("stopped at generation {}", self.generation));
+        }
+    }
+
+    /// Starts streaming metrics to a file, beginning with the current generation
+    pub fn record_metrics(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        let mut writer = MetricsWriter::create(path)?;
+        let cells = metrics::snapshot(&self.board);
+        writer.write(&Metrics::measure(
+            self.generation,
+            &self.board,
+            &cells,
+            None,
+        ))?;
+
+        self.metrics = Some((writer, cells));
+        Ok(())
+    }
+
+    /// Writes the metrics of the current generation, stopping with a message if that fails
+    fn record(&mut self) {
+        if let Some((writer, previous)) = &mut self.metrics {
+            let cells = metrics::snapshot(&self.board);
+            let metrics = Metrics::measure(self.generation, &self.board, &cells, Some(previous));
+            match writer.write(&metrics) {
+                Ok(()) => *previous = cells,
+                Err(err) => {
+                    self.message = Some(format!("stopped writing metrics: {}", err));
+                    self.metrics = None;
+                }
+            }
+        }
+    }
+
+    /// Moves the cursor by the given offset, staying inside the board. In drawing mode the cell
+    /// the cursor lands on is painted.
+    pub fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (width, height) = self.board.size();
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let x = self.cursor.0 as isize + dx;
+        let y = self.cursor.1 as isize + dy;
+
+        self.cursor = (
+            x.clamp(0, width as isize - 1) as usize,
+            y.clamp(0, height as isize - 1) as usize,
+        );
+
+        if self.drawing {
+            self.draw_cell();
+        }
+    }
+
+    /// Advances the cell under the cursor to its next state
+    pub fn cycle_cell(&mut self) {
+        let (x, y) = self.cursor;
+        self.edit("toggle", |board| board.cycle_cell(x, y));
+    }
+
+    /// Paints the cell under the cursor with the board's drawing state
+    pub fn draw_cell(&mut self) {
+        let (x, y) = self.cursor;
+        self.edit("draw", |board| board.draw_cell(x, y));
+    }
+
+    /// Changes the board in a way that can be undone
+    fn edit(&mut self, name: &'static str, change: impl FnOnce(&mut boards::Boards)) {
+        let before = history::states(&self.board);
+        change(&mut self.board);
+
+        self.history.record(Edit::since(name, &before, &self.board));
+        self.edited();
+    }
+
+    /// Reverts the last edit of the board, leaving the generation alone
+    pub fn undo(&mut self) {
+        self.message = Some(match self.history.undo(&mut self.board) {
+            Some(name) => format!("undid {}", name),
+            None => "nothing to undo".to_string(),
+        });
+        self.edited();
+    }
+
+    /// Makes the last undone edit again
+    pub fn redo(&mut self) {
+        self.message = Some(match self.history.redo(&mut self.board) {
+            Some(name) => format!("redid {}", name),
+            None => "nothing to redo".to_string(),
+        });
+        self.edited();
+    }
+
+    /// Kills every cell
+    pub fn clear(&mut self) {
+        self.edit("clear", |board| board.clear());
+    }
+
+    /// Replaces the board with a new soup, with the density and region of the current one if
+    /// there is one
+    pub fn randomize(&mut self) {
+        let soup = match self.soup {
+            Some(soup) => Soup::new(None, soup.density, soup.region),
+            None => Soup::new(None, 0.5, None),
+        };
+
+        self.edit("randomize", |board| {
+            board.clear();
+            soup.fill(board);
+        });
+        self.soup = Some(soup);
+    }
+
+    /// Starts selecting from the cursor, or stops if already selecting
+    pub fn toggle_selection(&mut self) {
+        self.selection = match self.selection {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// The selected rectangle as `(left, top, width, height)`, from the corner where selecting
+    /// started to the cursor
+    pub fn selected_region(&self) -> Option<(usize, usize, usize, usize)> {
+        let (x, y) = self.selection?;
+        let (left, top) = (x.min(self.cursor.0), y.min(self.cursor.1));
+
+        Some((
+            left,
+            top,
+            x.max(self.cursor.0) - left + 1,
+            y.max(self.cursor.1) - top + 1,
+        ))
+    }
+
+    /// Copies the selection to the clipboard and stops selecting
+    pub fn copy(&mut self) {
+        let Some((left, top, width, height)) = self.selected_region() else {
+            self.message = Some("nothing selected".to_string());
+            return;
+        };
+
+        let pattern = Pattern::from_region(&self.board, left, top, width, height);
+        self.clipboard = (format!("selection {}x{}", width, height), pattern);
+        self.selection = None;
+        self.message = Some(format!("copied {}x{}", width, height));
+    }
+
+    /// Copies the selection to the clipboard and clears it from the board
+    pub fn cut(&mut self) {
+        let Some((left, top, width, height)) = self.selected_region() else {
+            self.message = Some("nothing selected".to_string());
+            return;
+        };
+
+        self.copy();
+        self.edit("cut", |board| {
+            for y in top..top + height {
+                for x in left..left + width {
+                    board.clear_cell(x, y);
+                }
+            }
+        });
+
+        self.message = Some(format!("cut {}x{}", width, height));
+    }
+
+    /// Pastes the clipboard with its top left corner at the cursor
+    pub fn paste(&mut self) {
+        let pattern = self.clipboard.1.clone();
+        let ((x, y), mode) = (self.cursor, self.paste_mode);
+        self.edit("paste", |board| pattern.paste(board, x, y, mode));
+    }
+
+    /// Switches the clipboard to the next pattern in the library
+    pub fn next_pattern(&mut self) {
+        let entries = &self.library.entries;
+        let current = entries
+            .iter()
+            .position(|entry| entry.name == self.clipboard.0);
+        let entry = &entries[current.map_or(0, |i| (i + 1) % entries.len())];
+
+        self.clipboard = (entry.name.clone(), entry.pattern.clone());
+    }
+
+    /// Turns the clipboard a quarter turn clockwise
+    pub fn rotate_clipboard(&mut self) {
+        self.clipboard.1 = self.clipboard.1.rotate();
+    }
+
+    /// Mirrors the clipboard left to right, or top to bottom
+    pub fn flip_clipboard(&mut self, horizontal: bool) {
+        self.clipboard.1 = match horizontal {
+            true => self.clipboard.1.flip_horizontal(),
+            false => self.clipboard.1.flip_vertical(),
+        };
+    }
+
+    /// Writes the selection, or the clipboard when nothing is selected, to an RLE file in the
+    /// current directory
+    pub fn export_rle(&mut self) {
+        let pattern = match self.selected_region() {
+            Some((left, top, width, height)) => {
+                Pattern::from_region(&self.board, left, top, width, height)
+            }
+            None => self.clipboard.1.clone(),
+        };
+        let filename = self.filename("rle");
+
+        self.message = Some(match std::fs::write(&filename, pattern.to_rle()) {
+            Ok(()) => format!("wrote {}", filename),
+            Err(err) => format!("couldn't write RLE: {}", err),
+        });
+    }
+
+    /// The pattern selected in the library panel
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        let browser = self.browser.as_ref()?;
+        self.library.search(&browser.filter).nth(browser.selected)
+    }
+
+    /// Clears the board and puts the selected pattern in the middle of it
+    pub fn load_selected(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let (name, pattern) = (entry.name.clone(), entry.pattern.clone());
+
+        let (width, height) = self.board.size();
+        self.edit("load", |board| {
+            board.clear();
+            pattern.stamp(
+                board,
+                width.saturating_sub(pattern.width) / 2,
+                height.saturating_sub(pattern.height) / 2,
+            );
+        });
+
+        self.soup = None;
+        self.browser = None;
+        self.message = Some(format!("loaded {}", name));
+    }
+
+    /// Puts the selected pattern on the clipboard
+    pub fn copy_selected(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+
+        self.clipboard = (entry.name.clone(), entry.pattern.clone());
+        self.browser = None;
+    }
+
+    /// A file name for the current generation, including the seed of the soup if there is one
+    fn filename(&self, extension: &str) -> String {
+        let name = match self.soup {
+            Some(soup) => format!("soup-{}", soup.seed),
+            None => "board".to_string(),
+        };
+
+        format!("{}-gen{}.{}", name, self.generation, extension)
+    }
+
+    /// Writes the board to an image in the current directory, a PNG or SVG depending on the
+    /// extension
+    pub fn snapshot(&mut self, extension: &str) {
+        let filename = self.filename(extension);
+        let options = ImageOptions::default();
+
+        self.message = Some(
+            match export::snapshot(&self.board, Path::new(&filename), &options) {
+                Ok(()) => format!("wrote {}", filename),
+                Err(err) => format!("couldn't write image: {}", err),
+            },
+        );
+    }
+
+    /// Saves the board in the current directory, with the soup it was made from in a comment
+    pub fn save(&mut self) {
+        let filename = self.filename("txt");
+
+        let mut comments = Vec::new();
+        if let Some(soup) = self.soup {
+            comments.push(soup.to_string());
+        }
+        comments.push(format!("generation {}", self.generation));
+
+        self.message = Some(match self.board.save_to_file(&filename, &comments) {
+            Ok(()) => format!("saved to {}", filename),
+            Err(err) => format!("couldn't save: {}", err),
+        });
+    }
+
+    /// Starts cycle detection over from the edited board
+    fn edited(&mut self) {
+        self.detector.reset();
+        self.observe();
+
+        if let Some((_, previous)) = &mut self.metrics {
+            *previous = metrics::snapshot(&self.board);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Game;
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, Boards},
+    };
+
+    #[test]
+    pub fn test_undo_paste() {
+        let board = gol_board::Board::dead_state(8, 8, GOLMethod::Normal);
+        let mut game = Game::new(Boards::GolBoard(board));
+        game.cursor = (1, 1);
+
+        game.paste();
+        assert_eq!(5, population(&game));
+
+        game.undo();
+        assert_eq!(0, population(&game));
+        assert_eq!(Some("undid paste".to_string()), game.message);
+
+        game.redo();
+        assert_eq!(5, population(&game));
+    }
+
+    #[test]
+    pub fn test_move_cursor() {
+        let board = gol_board::Board::dead_state(4, 3, GOLMethod::Normal);
+        let mut game = Game::new(Boards::GolBoard(board));
+        game.move_cursor(10, -1);
+        assert_eq!((3, 0), game.cursor);
+
+        let board = gol_board::Board::dead_state(0, 0, GOLMethod::Normal);
+        let mut game = Game::new(Boards::GolBoard(board));
+        game.move_cursor(1, 1);
+        assert_eq!((0, 0), game.cursor);
+    }
+
+    fn population(game: &Game) -> usize {
+        let (width, height) = game.board.size();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|(x, y)| game.board.alive(*x, *y))
+            .count()
+    }
+}
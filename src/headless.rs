@@ -0,0 +1,40 @@
+use crate::game::Game;
+
+/// Advances the game without drawing it for up to `generations` generations, calling `each` with
+/// the starting generation and every one after it. With `until_stable` it stops as soon as the
+/// board has settled into a cycle.
+pub fn run<E>(
+    game: &mut Game,
+    generations: u64,
+    until_stable: bool,
+    mut each: impl FnMut(&Game) -> Result<(), E>,
+) -> Result<(), E> {
+    each(game)?;
+
+    for _ in 0..generations {
+        if until_stable && game.cycle().is_some() {
+            break;
+        }
+
+        game.update();
+        each(game)?;
+    }
+
+    Ok(())
+}
+
+/// A one line description of where the game has got to
+pub fn summary(game: &Game) -> String {
+    let mut summary = format!("generation {}", game.generation);
+
+    if let crate::boards::Boards::GolBoard(board) = &game.board {
+        summary.push_str(&format!(", population {}", board.population()));
+    }
+
+    match game.cycle() {
+        Some(cycle) => summary.push_str(&format!(", {}", cycle)),
+        None => summary.push_str(", no cycle found"),
+    }
+
+    summary
+}
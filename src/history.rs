@@ -0,0 +1,167 @@
+use crate::boards::Boards;
+
+/// Edits kept for undoing, the oldest are dropped after this many
+const LIMIT: usize = 1000;
+
+/// The state of every cell of a board, row by row, to find what an edit changed
+pub fn states(board: &Boards) -> Vec<u8> {
+    let (width, height) = board.size();
+    let mut states = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            states.push(board.state(x, y));
+        }
+    }
+
+    states
+}
+
+/// The cells changed by one edit of the board, with their states before and after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// What the edit was, such as `paste` or `clear`
+    pub name: &'static str,
+    cells: Vec<(usize, usize, u8, u8)>,
+}
+
+impl Edit {
+    /// The edit that turned the cell states `before` into the board as it is now
+    pub fn since(name: &'static str, before: &[u8], board: &Boards) -> Edit {
+        let (width, _) = board.size();
+        let cells = states(board)
+            .into_iter()
+            .zip(before)
+            .enumerate()
+            .filter(|(_, (after, before))| after != *before)
+            .map(|(i, (after, before))| (i % width, i / width, *before, after))
+            .collect();
+
+        Edit { name, cells }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn undo(&self, board: &mut Boards) {
+        for (x, y, before, _) in &self.cells {
+            board.set_state(*x, *y, *before);
+        }
+    }
+
+    fn redo(&self, board: &mut Boards) {
+        for (x, y, _, after) in &self.cells {
+            board.set_state(*x, *y, *after);
+        }
+    }
+}
+
+/// Edits made to the board that can be undone and redone. Generations passing aren't edits, so
+/// undoing leaves the generation alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    /// Remembers an edit, unless it changed nothing, and forgets the edits that were undone
+    pub fn record(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+
+        if self.undo.len() == LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    /// Reverts the last edit, returning its name
+    pub fn undo(&mut self, board: &mut Boards) -> Option<&'static str> {
+        let edit = self.undo.pop()?;
+        edit.undo(board);
+
+        let name = edit.name;
+        self.redo.push(edit);
+        Some(name)
+    }
+
+    /// Makes the last undone edit again, returning its name
+    pub fn redo(&mut self, board: &mut Boards) -> Option<&'static str> {
+        let edit = self.redo.pop()?;
+        edit.redo(board);
+
+        let name = edit.name;
+        self.undo.push(edit);
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{states, Edit, History};
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, wire_board, Boards},
+    };
+
+    #[test]
+    pub fn test_undo_redo() {
+        let mut board = Boards::GolBoard(gol_board::Board::dead_state(3, 3, GOLMethod::Normal));
+        let mut history = History::default();
+
+        let before = states(&board);
+        board.draw_cell(1, 1);
+        history.record(Edit::since("draw", &before, &board));
+
+        let before = states(&board);
+        board.cycle_cell(1, 1);
+        board.cycle_cell(2, 0);
+        history.record(Edit::since("toggle", &before, &board));
+
+        // An edit that changes nothing isn't remembered
+        history.record(Edit::since("clear", &states(&board), &board));
+
+        assert_eq!(Some("toggle"), history.undo(&mut board));
+        assert!(board.alive(1, 1) && !board.alive(2, 0));
+        assert_eq!(Some("draw"), history.undo(&mut board));
+        assert_eq!(vec![0; 9], states(&board));
+        assert_eq!(None, history.undo(&mut board));
+
+        assert_eq!(Some("draw"), history.redo(&mut board));
+        let before = states(&board);
+        board.draw_cell(0, 0);
+        history.record(Edit::since("draw", &before, &board));
+
+        // A new edit forgets the undone ones
+        assert_eq!(None, history.redo(&mut board));
+        assert_eq!(Some("draw"), history.undo(&mut board));
+        assert!(board.alive(1, 1) && !board.alive(0, 0));
+    }
+
+    #[test]
+    pub fn test_wire_states() {
+        let mut board = Boards::WireBoard(wire_board::Board::empty_state(1, 1));
+        let mut history = History::default();
+
+        let before = states(&board);
+        board.cycle_cell(0, 0);
+        board.cycle_cell(0, 0);
+        history.record(Edit::since("toggle", &before, &board));
+        assert_eq!(Some(wire_board::Cell::Head), wire(&board));
+
+        history.undo(&mut board);
+        assert_eq!(Some(wire_board::Cell::Empty), wire(&board));
+        history.redo(&mut board);
+        assert_eq!(Some(wire_board::Cell::Head), wire(&board));
+    }
+
+    fn wire(board: &Boards) -> Option<wire_board::Cell> {
+        match board {
+            Boards::WireBoard(wire) => Some(wire.get(0, 0)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::{io, path::Path};
+
+use crate::pattern::Pattern;
+
+/// How the pixels of an image, or the lines of a text board, become cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Pixels darker than this (0 to 255) are live cells
+    pub threshold: u8,
+    /// Width and height in pixels of the square each cell is made from, averaging its pixels.
+    /// Loading an image panics if it is 0.
+    pub scale: usize,
+    /// Make light pixels live cells instead of dark ones
+    pub invert: bool,
+    /// Fill out lines of a text board shorter than the longest with dead cells instead of
+    /// rejecting the board
+    pub pad: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            threshold: 128,
+            scale: 1,
+            invert: false,
+            pad: false,
+        }
+    }
+}
+
+/// The brightness of each pixel of an image, row by row, from 0 for black to 255 for white
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Grayscale {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Whether a file is an image that `load_image` can read, going by its extension
+pub fn is_image(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("png") | Some("pbm")
+    )
+}
+
+/// Loads a PNG or PBM image as a pattern the size of the image divided by the scale
+pub fn load_image(path: &Path, options: &ImportOptions) -> io::Result<Pattern> {
+    let bytes = std::fs::read(path)?;
+    let image = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("pbm") => decode_pbm(&bytes)?,
+        _ => decode_png(&bytes)?,
+    };
+
+    Ok(threshold(&image, options))
+}
+
+fn threshold(image: &Grayscale, options: &ImportOptions) -> Pattern {
+    let scale = options.scale;
+    let width = image.width.div_ceil(scale);
+    let height = image.height.div_ceil(scale);
+
+    let mut pattern = Pattern::empty(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let (mut total, mut count) = (0, 0);
+            for py in y * scale..((y + 1) * scale).min(image.height) {
+                for px in x * scale..((x + 1) * scale).min(image.width) {
+                    total += image.pixels[py * image.width + px] as usize;
+                    count += 1;
+                }
+            }
+
+            let dark = total < options.threshold as usize * count;
+            pattern.set(x, y, dark != options.invert);
+        }
+    }
+
+    pattern
+}
+
+fn decode_png(bytes: &[u8]) -> io::Result<Grayscale> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| invalid(&err.to_string()))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| invalid(&err.to_string()))?;
+
+    let channels = info.color_type.samples();
+    let pixels = buffer[..info.buffer_size()]
+        .chunks(info.line_size)
+        .flat_map(|line| line[..info.width as usize * channels].chunks(channels))
+        .map(|pixel| {
+            let (luma, alpha) = match pixel {
+                [gray] => (*gray as u32, 255),
+                [gray, alpha] => (*gray as u32, *alpha as u32),
+                [r, g, b] => (luma(*r, *g, *b), 255),
+                [r, g, b, alpha] => (luma(*r, *g, *b), *alpha as u32),
+                _ => (255, 255),
+            };
+
+            // Transparent pixels count as white
+            ((luma * alpha + 255 * (255 - alpha)) / 255) as u8
+        })
+        .collect();
+
+    Ok(Grayscale {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+fn luma(r: u8, g: u8, b: u8) -> u32 {
+    (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000
+}
+
+/// Reads the whitespace separated header fields of a PBM, skipping `#` comments, leaving the
+/// reader on the byte after the last field's single trailing whitespace
+fn pbm_field(bytes: &mut impl Iterator<Item = u8>) -> io::Result<String> {
+    let mut field = String::new();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'#' => {
+                for byte in bytes.by_ref() {
+                    if byte == b'\n' {
+                        break;
+                    }
+                }
+            }
+            _ if byte.is_ascii_whitespace() => {
+                if !field.is_empty() {
+                    return Ok(field);
+                }
+            }
+            _ => field.push(byte as char),
+        }
+    }
+
+    if field.is_empty() {
+        Err(invalid("the PBM header ends early"))
+    } else {
+        Ok(field)
+    }
+}
+
+/// Decodes a plain (`P1`) or raw (`P4`) portable bitmap, where 1 is black
+fn decode_pbm(bytes: &[u8]) -> io::Result<Grayscale> {
+    let mut reader = bytes.iter().copied();
+    let magic = pbm_field(&mut reader)?;
+    let size = |field: String| {
+        field
+            .parse::<usize>()
+            .map_err(|_| invalid("the PBM size isn't a number"))
+    };
+    let width = size(pbm_field(&mut reader)?)?;
+    let height = size(pbm_field(&mut reader)?)?;
+    if width == 0 || height == 0 {
+        return Err(invalid("the PBM has no pixels"));
+    }
+    let too_large = || invalid("the PBM is too large");
+    let pixels = width.checked_mul(height).ok_or_else(too_large)?;
+
+    let bits: Vec<bool> = match magic.as_str() {
+        "P1" => reader
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .take(pixels)
+            .map(|byte| byte == b'1')
+            .collect(),
+        "P4" => {
+            let data: Vec<u8> = reader.collect();
+            let row_bytes = width.div_ceil(8);
+            if data.len() < row_bytes.checked_mul(height).ok_or_else(too_large)? {
+                return Err(invalid("the PBM has fewer pixels than its size"));
+            }
+
+            data.chunks(row_bytes)
+                .take(height)
+                .flat_map(|row| (0..width).map(move |x| row[x / 8] & (0x80 >> (x % 8)) != 0))
+                .collect()
+        }
+        _ => return Err(invalid("only P1 and P4 bitmaps are supported")),
+    };
+
+    if bits.len() != pixels {
+        return Err(invalid("the PBM has fewer pixels than its size"));
+    }
+
+    Ok(Grayscale {
+        width,
+        height,
+        pixels: bits
+            .iter()
+            .map(|black| if *black { 0 } else { 255 })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_pbm, decode_png, threshold, ImportOptions};
+    use crate::pattern::Pattern;
+
+    #[test]
+    pub fn test_pbm() {
+        let plain = decode_pbm(b"P1\n# a glider\n3 3\n0 1 0\n0 0 1\n111\n").unwrap();
+        let raw = decode_pbm(b"P4 3 3\n\x40\x20\xe0").unwrap();
+
+        assert_eq!(plain, raw);
+        assert_eq!(
+            Pattern::from_rows(&[".O.", "..O", "OOO"]),
+            threshold(&plain, &ImportOptions::default())
+        );
+        assert!(decode_pbm(b"P1 3 3 0 1 0").is_err());
+        assert!(decode_pbm(b"P4\n16 2\n\x00").is_err());
+        assert!(decode_pbm(b"P4\n0 2\n\x00").is_err());
+        assert!(decode_pbm(b"P4\n18446744073709551615 2\n\x00").is_err());
+    }
+
+    #[test]
+    pub fn test_png_scale() {
+        // A 4x2 grayscale image: a dark 2x2 square on the left, a light one on the right
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 4, 2);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[10, 60, 200, 250, 90, 30, 220, 100])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let image = decode_png(&bytes).unwrap();
+        let scaled = ImportOptions {
+            scale: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Pattern::from_rows(&["OO..", "OO.O"]),
+            threshold(&image, &ImportOptions::default())
+        );
+        assert_eq!(Pattern::from_rows(&["O."]), threshold(&image, &scaled));
+        assert_eq!(
+            Pattern::from_rows(&[".O"]),
+            threshold(
+                &image,
+                &ImportOptions {
+                    invert: true,
+                    ..scaled
+                }
+            )
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    sync::mpsc::{channel, Receiver, RecvError, Sender},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
     time::Duration, thread,
 };
 
@@ -13,25 +13,31 @@ pub struct Events {
 }
 
 impl Events {
-    pub fn new(tick_rate: Duration) -> Events {
+    pub fn new() -> Events {
         let (tx, rx) = channel();
 
         let event_tx = tx.clone();
 
         thread::spawn(move || loop {
-            if crossterm::event::poll(tick_rate).unwrap() {
-                if let event::Event::Key(key) = event::read().unwrap() {
-                    let key = Key::from(key);
-                    event_tx.send(InputEvent::Input(key)).unwrap();
-                }
+            let event = match event::read() {
+                Ok(event::Event::Key(key)) => InputEvent::Input(Key::from(key)),
+                Ok(_) => continue,
+                Err(err) => InputEvent::Error(err),
+            };
+
+            if event_tx.send(event).is_err() {
+                break;
             }
-            event_tx.send(InputEvent::Tick).unwrap();
         });
 
         Events { rx, _tx: tx }
     }
 
-    pub fn next(&self) -> Result<InputEvent, RecvError> {
-        self.rx.recv()
+    /// The next key pressed, or a tick if none is pressed within the timeout
+    pub fn next(&self, timeout: Duration) -> InputEvent {
+        match self.rx.recv_timeout(timeout) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => InputEvent::Tick,
+        }
     }
 }
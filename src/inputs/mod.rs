@@ -3,12 +3,14 @@ pub mod events;
 pub enum InputEvent {
     /// Input event occured
     Input(Key),
-    /// Tick event occured
+    /// No input arrived in time
     Tick,
+    /// Input couldn't be read
+    Error(std::io::Error),
 }
 
 use crossterm::event;
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 /// Represents an key.
 /// Taken from spotify-tui
@@ -130,6 +132,65 @@ impl fmt::Display for Key {
   }
 }
 
+/// Parses keys as `Display` writes them, such as `q`, `<Space>`, `<Ctrl+z>` or
+/// `<Left Arrow Key>`. The angle brackets can be left out, names of keys aren't case sensitive
+/// and `-` can be used instead of `+`, so `ctrl-z` and `left` work too.
+impl FromStr for Key {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let name = s.strip_prefix('<').and_then(|name| name.strip_suffix('>')).unwrap_or(s);
+    let single = |name: &str| {
+      let mut chars = name.chars();
+      match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ if name.eq_ignore_ascii_case("space") => Some(' '),
+        _ => None,
+      }
+    };
+
+    if let Some(c) = single(name) {
+      return Ok(Key::Char(c));
+    }
+
+    let lower = name.to_lowercase();
+    for (prefix, modifier) in [("ctrl", Key::Ctrl as fn(char) -> Key), ("alt", Key::Alt)] {
+      let Some(rest) = lower.strip_prefix(prefix) else {
+        continue;
+      };
+
+      if rest.starts_with(['+', '-']) {
+        if let Some(c) = single(&name[prefix.len() + 1..]) {
+          return Ok(modifier(c));
+        }
+      }
+    }
+
+    let key = match lower.trim_end_matches(" arrow key") {
+      "enter" => Key::Enter,
+      "tab" => Key::Tab,
+      "backspace" => Key::Backspace,
+      "esc" => Key::Esc,
+      "left" => Key::Left,
+      "right" => Key::Right,
+      "up" => Key::Up,
+      "down" => Key::Down,
+      "ins" | "insert" => Key::Ins,
+      "delete" => Key::Delete,
+      "home" => Key::Home,
+      "end" => Key::End,
+      "pageup" => Key::PageUp,
+      "pagedown" => Key::PageDown,
+      function => match function.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        Some(n @ 0..=12) => Key::from_f(n),
+        _ => return Err(format!("{} is not a key such as q, space, ctrl+z or f1", s)),
+      },
+    };
+
+    Ok(key)
+  }
+}
+
 impl From<event::KeyEvent> for Key {
   fn from(key_event: event::KeyEvent) -> Self {
     match key_event {
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{inputs::Key, GOLError};
+
+/// The key that quits from anywhere in the UI, whatever the config says
+pub const QUIT_KEY: Key = Key::Ctrl('c');
+
+/// Something the user can do in the UI with a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Pause,
+    Step,
+    StepBack,
+    Reverse,
+    Faster,
+    Slower,
+    Goto,
+    Draw,
+    DrawCell,
+    ToggleCell,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    Select,
+    Copy,
+    Cut,
+    Paste,
+    PasteMode,
+    NextPattern,
+    Rotate,
+    FlipHorizontal,
+    FlipVertical,
+    Library,
+    Undo,
+    Redo,
+    Clear,
+    Randomize,
+    Save,
+    ExportPng,
+    ExportSvg,
+    ExportRle,
+    Help,
+}
+
+impl Action {
+    /// Every action with its default keys, in the order the help lists them
+    const DEFAULTS: [(Action, &'static [Key]); 34] = [
+        (Action::Quit, &[Key::Char('q'), Key::Ctrl('c')]),
+        (Action::Pause, &[Key::Char('p'), Key::Char(' ')]),
+        (Action::Step, &[Key::Char('n')]),
+        (Action::StepBack, &[Key::Char('b')]),
+        (Action::Reverse, &[Key::Char('r')]),
+        (Action::Faster, &[Key::Char('+'), Key::Char('=')]),
+        (Action::Slower, &[Key::Char('-')]),
+        (Action::Goto, &[Key::Char('g')]),
+        (Action::Draw, &[Key::Char('d')]),
+        (Action::DrawCell, &[Key::Char('c')]),
+        (Action::ToggleCell, &[Key::Enter]),
+        (Action::CursorLeft, &[Key::Left]),
+        (Action::CursorRight, &[Key::Right]),
+        (Action::CursorUp, &[Key::Up]),
+        (Action::CursorDown, &[Key::Down]),
+        (Action::Select, &[Key::Char('v')]),
+        (Action::Copy, &[Key::Char('y')]),
+        (Action::Cut, &[Key::Char('x')]),
+        (Action::Paste, &[Key::Char('t')]),
+        (Action::PasteMode, &[Key::Char('m')]),
+        (Action::NextPattern, &[Key::Char('T')]),
+        (Action::Rotate, &[Key::Char('o')]),
+        (Action::FlipHorizontal, &[Key::Char('f')]),
+        (Action::FlipVertical, &[Key::Char('F')]),
+        (Action::Library, &[Key::Char('l')]),
+        (Action::Undo, &[Key::Ctrl('z')]),
+        (Action::Redo, &[Key::Ctrl('y')]),
+        (Action::Clear, &[Key::Char('C')]),
+        (Action::Randomize, &[Key::Char('N')]),
+        (Action::Save, &[Key::Char('s')]),
+        (Action::ExportPng, &[Key::Char('e')]),
+        (Action::ExportSvg, &[Key::Char('E')]),
+        (Action::ExportRle, &[Key::Char('R')]),
+        (Action::Help, &[Key::Char('?')]),
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Pause => "pause or resume",
+            Action::Step => "step a generation while paused",
+            Action::StepBack => "step back a generation while paused",
+            Action::Reverse => "run backwards on reversible boards",
+            Action::Faster => "run generations twice as fast",
+            Action::Slower => "run generations half as fast",
+            Action::Goto => "run to a generation without drawing",
+            Action::Draw => "draw while moving the cursor",
+            Action::DrawCell => "draw the cell under the cursor",
+            Action::ToggleCell => "cycle the cell under the cursor",
+            Action::CursorLeft => "move the cursor left",
+            Action::CursorRight => "move the cursor right",
+            Action::CursorUp => "move the cursor up",
+            Action::CursorDown => "move the cursor down",
+            Action::Select => "start or stop selecting",
+            Action::Copy => "copy the selection",
+            Action::Cut => "cut the selection",
+            Action::Paste => "paste at the cursor",
+            Action::PasteMode => "switch between or, xor and copy pasting",
+            Action::NextPattern => "put the next library pattern on the clipboard",
+            Action::Rotate => "rotate the clipboard",
+            Action::FlipHorizontal => "flip the clipboard left to right",
+            Action::FlipVertical => "flip the clipboard top to bottom",
+            Action::Library => "browse the pattern library",
+            Action::Undo => "undo an edit",
+            Action::Redo => "redo an edit",
+            Action::Clear => "clear the board",
+            Action::Randomize => "fill the board with a new soup",
+            Action::Save => "save the board",
+            Action::ExportPng => "write a PNG of the board",
+            Action::ExportSvg => "write an SVG of the board",
+            Action::ExportRle => "write the selection or clipboard as RLE",
+            Action::Help => "show or hide this help",
+        }
+    }
+}
+
+/// A key or a list of keys for an action in the `[keys]` table of the config file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Which keys do which actions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap {
+            bindings: Action::DEFAULTS
+                .iter()
+                .map(|(action, keys)| (*action, keys.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The default keys, with those of each action in the `[keys]` table of the config file
+    /// replaced. A key given to an action is taken away from any other, and giving one key to two
+    /// actions is an error. [`QUIT_KEY`] always quits.
+    pub fn from_keys(keys: &HashMap<Action, Keys>) -> Result<Keymap, GOLError> {
+        let mut keymap = Keymap::default();
+        let mut claimed: HashMap<Key, Action> = HashMap::new();
+        for (action, keys) in keys {
+            let keys = match keys {
+                Keys::One(key) => std::slice::from_ref(key),
+                Keys::Many(keys) => keys.as_slice(),
+            };
+            let keys = keys
+                .iter()
+                .map(|key| key.parse::<Key>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(GOLError::InvalidConfig)?;
+
+            for key in &keys {
+                let other = match claimed.insert(*key, *action) {
+                    _ if *key == QUIT_KEY && *action != Action::Quit => Action::Quit,
+                    Some(other) if other != *action => other,
+                    _ => continue,
+                };
+                return Err(GOLError::InvalidConfig(format!(
+                    "{} is given to both \"{}\" and \"{}\"",
+                    key,
+                    other.description(),
+                    action.description()
+                )));
+            }
+
+            for (_, bound) in keymap.bindings.iter_mut() {
+                bound.retain(|key| !keys.contains(key));
+            }
+            for (bound_action, bound) in keymap.bindings.iter_mut() {
+                if bound_action == action {
+                    *bound = keys.clone();
+                }
+            }
+        }
+
+        for (action, bound) in keymap.bindings.iter_mut() {
+            if *action == Action::Quit && !bound.contains(&QUIT_KEY) {
+                bound.push(QUIT_KEY);
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// The action a key does, if any
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    /// Every action with its keys, in the order the help lists them
+    pub fn bindings(&self) -> &[(Action, Vec<Key>)] {
+        &self.bindings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Keymap};
+    use crate::{config::Config, inputs::Key};
+
+    #[test]
+    pub fn test_parse_keys() {
+        assert_eq!(Ok(Key::Char('q')), "q".parse());
+        assert_eq!(Ok(Key::Char(' ')), "<Space>".parse());
+        assert_eq!(Ok(Key::Ctrl('z')), "ctrl-z".parse());
+        assert_eq!(Ok(Key::Alt('X')), "Alt+X".parse());
+        assert_eq!(Ok(Key::Left), "<Left Arrow Key>".parse());
+        assert_eq!(Ok(Key::F5), "f5".parse());
+        assert!("f13".parse::<Key>().is_err());
+        assert!("hyper+q".parse::<Key>().is_err());
+
+        // Every key reads back from how it is shown
+        for (_, keys) in Keymap::default().bindings() {
+            for key in keys {
+                assert_eq!(Ok(*key), key.to_string().parse());
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_from_toml() {
+        let from_toml = |config| Config::from_toml(config).and_then(|config| config.keymap());
+        let keymap = from_toml("[keys]\npause = \"q\"\nquit = [\"ctrl+q\", \"esc\"]\n").unwrap();
+
+        assert_eq!(Some(Action::Pause), keymap.action(Key::Char('q')));
+        assert_eq!(None, keymap.action(Key::Char('p')));
+        assert_eq!(Some(Action::Quit), keymap.action(Key::Esc));
+        assert_eq!(Some(Action::Quit), keymap.action(Key::Ctrl('c')));
+        assert_eq!(Some(Action::Undo), keymap.action(Key::Ctrl('z')));
+
+        // Quit keeps ctrl-c, even when all its other keys go elsewhere
+        let keymap = from_toml("[keys]\npause = \"q\"\n").unwrap();
+        assert_eq!(Some(Action::Quit), keymap.action(Key::Ctrl('c')));
+        assert!(from_toml("[keys]\nquit = []\n").is_ok());
+
+        assert!(from_toml("[keys]\npause = \"x\"\nstep = [\"n\", \"x\"]\n").is_err());
+        assert!(from_toml("[keys]\npause = \"ctrl-c\"\n").is_err());
+        assert!(from_toml("[keys]\nfly = \"w\"\n").is_err());
+        assert!(from_toml("[keys]\npause = \"hyper+p\"\n").is_err());
+        assert_eq!(Keymap::default(), from_toml("").unwrap());
+    }
+}
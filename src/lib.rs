@@ -1,12 +1,93 @@
+//! Game of Life and other cellular automata, run in the terminal by the `gol` binary. The boards
+//! can also be used on their own, without the terminal:
+//!
+//! ```
+//! use game_of_life::{args::GOLMethod, boards::gol_board::Board};
+//!
+//! let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+//! let board = Board::from_cells(8, 8, GOLMethod::Normal, glider);
+//!
+//! // After four generations the glider has moved a cell down and to the right
+//! let later = board.generations().nth(4).unwrap();
+//! let moved: Vec<_> = glider.iter().map(|(x, y)| (x + 1, y + 1)).collect();
+//! assert_eq!(moved, later.live_cells().collect::<Vec<_>>());
+//! ```
+
+pub mod analysis;
 pub mod args;
+mod clock;
 pub mod commands;
+mod config;
+pub mod export;
 mod game;
+mod headless;
+mod history;
+pub mod import;
 mod inputs;
+mod keymap;
+pub mod library;
+pub mod metrics;
+pub mod pattern;
+pub mod scene;
+pub mod soup;
 pub mod ui;
 pub mod boards;
 
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
 #[derive(Debug)]
 pub enum GOLError {
-    InvalidFile,
-    UnknownError
+    InvalidRule(String),
+    InvalidPattern(String),
+    InvalidConfig(String),
+    /// A file that isn't written as it should be, at a line and column counted from 1
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A file that couldn't be read or written
+    Io { path: PathBuf, source: io::Error },
+    /// The terminal couldn't be set up, drawn on or read from
+    Terminal(io::Error),
+}
+
+impl GOLError {
+    pub fn io(path: &Path, source: io::Error) -> GOLError {
+        GOLError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for GOLError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GOLError::InvalidRule(rule) => write!(f, "invalid rule {}", rule),
+            GOLError::InvalidPattern(pattern) => write!(f, "invalid pattern {}", pattern),
+            GOLError::InvalidConfig(message) => write!(f, "invalid config: {}", message),
+            GOLError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            GOLError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            GOLError::Terminal(source) => write!(f, "terminal error: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for GOLError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GOLError::Io { source, .. } | GOLError::Terminal(source) => Some(source),
+            _ => None,
+        }
+    }
 }
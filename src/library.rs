@@ -0,0 +1,448 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    analysis::census,
+    boards::gol_board,
+    import::{self, ImportOptions},
+    pattern::Pattern,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Gun,
+    Puffer,
+    Methuselah,
+    /// Patterns from the user's directory that aren't in a category's folder
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::StillLife,
+        Category::Oscillator,
+        Category::Spaceship,
+        Category::Gun,
+        Category::Puffer,
+        Category::Methuselah,
+        Category::Other,
+    ];
+
+    /// The name of the category's folder in the user's pattern directory
+    pub fn folder(&self) -> &'static str {
+        match self {
+            Category::StillLife => "still-lifes",
+            Category::Oscillator => "oscillators",
+            Category::Spaceship => "spaceships",
+            Category::Gun => "guns",
+            Category::Puffer => "puffers",
+            Category::Methuselah => "methuselahs",
+            Category::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Category::StillLife => write!(f, "still life"),
+            Category::Oscillator => write!(f, "oscillator"),
+            Category::Spaceship => write!(f, "spaceship"),
+            Category::Gun => write!(f, "gun"),
+            Category::Puffer => write!(f, "puffer"),
+            Category::Methuselah => write!(f, "methuselah"),
+            Category::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// The patterns built into the library. Objects the census can name leave their rows empty and
+/// use its pattern.
+const BUILT_IN: &[(&str, Category, &str, &[&str])] = &[
+    (
+        "block",
+        Category::StillLife,
+        "The most common still life",
+        &[],
+    ),
+    (
+        "beehive",
+        Category::StillLife,
+        "The second most common still life",
+        &[],
+    ),
+    ("loaf", Category::StillLife, "A seven cell still life", &[]),
+    ("boat", Category::StillLife, "A five cell still life", &[]),
+    (
+        "tub",
+        Category::StillLife,
+        "Four cells around an empty one",
+        &[],
+    ),
+    (
+        "ship",
+        Category::StillLife,
+        "A boat with an extra cell",
+        &[],
+    ),
+    ("pond", Category::StillLife, "A ring of eight cells", &[]),
+    (
+        "long boat",
+        Category::StillLife,
+        "A boat stretched by one cell",
+        &[],
+    ),
+    (
+        "barge",
+        Category::StillLife,
+        "A tub stretched by one cell",
+        &[],
+    ),
+    (
+        "blinker",
+        Category::Oscillator,
+        "Period 2, the smallest oscillator",
+        &[],
+    ),
+    (
+        "toad",
+        Category::Oscillator,
+        "Period 2, two shifted rows of three",
+        &[],
+    ),
+    (
+        "beacon",
+        Category::Oscillator,
+        "Period 2, two blocks touching at a corner",
+        &[],
+    ),
+    (
+        "pulsar",
+        Category::Oscillator,
+        "Period 3, the most common period 3 oscillator",
+        &[],
+    ),
+    (
+        "pentadecathlon",
+        Category::Oscillator,
+        "Period 15, from a row of ten cells",
+        &[],
+    ),
+    (
+        "glider",
+        Category::Spaceship,
+        "Moves diagonally one cell every four generations",
+        &[],
+    ),
+    (
+        "lightweight spaceship",
+        Category::Spaceship,
+        "Moves orthogonally two cells every four generations",
+        &[],
+    ),
+    (
+        "middleweight spaceship",
+        Category::Spaceship,
+        "A lightweight spaceship one cell longer",
+        &[],
+    ),
+    (
+        "heavyweight spaceship",
+        Category::Spaceship,
+        "A lightweight spaceship two cells longer",
+        &[],
+    ),
+    (
+        "gosper glider gun",
+        Category::Gun,
+        "The first gun found, fires a glider every 30 generations",
+        &[
+            "........................O...........",
+            "......................O.O...........",
+            "............OO......OO............OO",
+            "...........O...O....OO............OO",
+            "OO........O.....O...OO..............",
+            "OO........O...O.OO....O.O...........",
+            "..........O.....O.......O...........",
+            "...........O...O....................",
+            "............OO......................",
+        ],
+    ),
+    (
+        "block-laying switch engine",
+        Category::Puffer,
+        "Ten cells that become a switch engine leaving blocks behind it forever",
+        &[
+            "......O.", "....O.OO", "....O.O.", "....O...", "..O.....", "O.O.....",
+        ],
+    ),
+    (
+        "five by five infinite growth",
+        Category::Puffer,
+        "The smallest square that grows forever, also into a block-laying switch engine",
+        &["OOO.O", "O....", "...OO", ".OO.O", "O.O.O"],
+    ),
+    (
+        "r-pentomino",
+        Category::Methuselah,
+        "Five cells that take 1103 generations to settle",
+        &[".OO", "OO.", ".O."],
+    ),
+    (
+        "diehard",
+        Category::Methuselah,
+        "Seven cells that vanish after 130 generations",
+        &["......O.", "OO......", ".O...OOO"],
+    ),
+    (
+        "acorn",
+        Category::Methuselah,
+        "Seven cells that take 5206 generations to settle",
+        &[".O.....", "...O...", "OO..OOO"],
+    ),
+    (
+        "pi-heptomino",
+        Category::Methuselah,
+        "Seven cells that settle after 173 generations",
+        &["OOO", "O.O", "O.O"],
+    ),
+    (
+        "b-heptomino",
+        Category::Methuselah,
+        "Seven cells that settle after 148 generations",
+        &["O.OO", "OOO.", ".O.."],
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub category: Category,
+    pub description: String,
+    pub pattern: Pattern,
+}
+
+impl Entry {
+    /// Whether every word of the filter is in the name, category or description, ignoring case
+    pub fn matches(&self, filter: &str) -> bool {
+        let text = format!("{} {} {}", self.name, self.category, self.description).to_lowercase();
+
+        filter
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+    }
+}
+
+/// Named patterns to browse, load and stamp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    pub entries: Vec<Entry>,
+}
+
+/// Lowercases a pattern name and treats `-` and `_` as spaces, so that `long-boat` finds
+/// `long boat`
+fn normalize(name: &str) -> String {
+    name.replace(['-', '_'], " ").to_lowercase()
+}
+
+impl Library {
+    pub fn built_in() -> Library {
+        let entries = BUILT_IN
+            .iter()
+            .map(|(name, category, description, rows)| Entry {
+                name: name.to_string(),
+                category: *category,
+                description: description.to_string(),
+                pattern: match rows {
+                    [] => census::known_object(name).unwrap(),
+                    rows => Pattern::from_rows(rows),
+                },
+            })
+            .collect();
+
+        Library { entries }
+    }
+
+    /// The directory the user's own patterns are loaded from
+    pub fn user_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("gol").join("patterns"))
+    }
+
+    /// The built in patterns followed by those in the user's directory, with the files that
+    /// couldn't be loaded
+    pub fn load() -> (Library, Vec<PathBuf>) {
+        let mut library = Library::built_in();
+        let skipped = match Library::user_dir() {
+            Some(dir) => library.load_dir(&dir),
+            None => Vec::new(),
+        };
+
+        (library, skipped)
+    }
+
+    /// Adds the pattern files in a directory, and in its folders named after a category, returning
+    /// the files that couldn't be loaded. The first `#` comment of a text file is its description.
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<PathBuf> {
+        let mut skipped = Vec::new();
+
+        for category in Category::ALL {
+            let folder = match category {
+                Category::Other => dir.to_path_buf(),
+                _ => dir.join(category.folder()),
+            };
+            let Ok(files) = std::fs::read_dir(&folder) else {
+                continue;
+            };
+
+            let mut paths: Vec<PathBuf> = files
+                .filter_map(|file| file.ok().map(|file| file.path()))
+                .filter(|path| path.is_file())
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                match load_entry(&path, category) {
+                    Some(entry) => self.entries.push(entry),
+                    None => skipped.push(path),
+                }
+            }
+        }
+
+        skipped
+    }
+
+    /// Finds a pattern by name, ignoring case and treating `-` and `_` as spaces
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        let name = normalize(name);
+        self.entries
+            .iter()
+            .find(|entry| normalize(&entry.name) == name)
+    }
+
+    /// The entries matching a filter, in library order
+    pub fn search<'a>(&'a self, filter: &'a str) -> impl Iterator<Item = &'a Entry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.matches(filter))
+    }
+}
+
+fn load_entry(path: &Path, category: Category) -> Option<Entry> {
+    let name = path.file_stem()?.to_str()?.to_string();
+
+    let (pattern, description) = if import::is_image(path) {
+        let pattern = import::load_image(path, &ImportOptions::default()).ok()?;
+        (pattern, String::new())
+    } else {
+        let board = gol_board::Board::load_from_file(path, false).ok()?;
+        let description = std::fs::read_to_string(path)
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix('#'))
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        (Pattern::from_board(&board), description)
+    };
+
+    Some(Entry {
+        name,
+        category,
+        description,
+        pattern,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, Library};
+    use crate::{
+        analysis::census::{Kind, Object},
+        args::GOLMethod,
+    };
+
+    #[test]
+    pub fn test_built_in() {
+        let library = Library::built_in();
+
+        for entry in &library.entries {
+            let kind =
+                Object::classify(&entry.pattern, GOLMethod::Normal, &Default::default()).kind;
+            match entry.category {
+                Category::StillLife => assert_eq!(Kind::StillLife, kind, "{}", entry.name),
+                Category::Oscillator => {
+                    assert!(matches!(kind, Kind::Oscillator { .. }), "{}", entry.name)
+                }
+                Category::Spaceship => {
+                    assert!(matches!(kind, Kind::Spaceship { .. }), "{}", entry.name)
+                }
+                _ => assert_eq!(Kind::Unknown, kind, "{}", entry.name),
+            }
+        }
+
+        // The gun has made two gliders after 60 generations
+        let gun = library.get("Gosper-glider-gun").unwrap();
+        let mut board = gun.pattern.to_board(20, GOLMethod::Normal);
+        for _ in 0..60 {
+            board = board.next_state();
+        }
+        assert_eq!(gun.pattern.population() + 2 * 5, board.population());
+
+        let diehard = library.get("diehard").unwrap();
+        let mut board = diehard.pattern.to_board(30, GOLMethod::Normal);
+        for _ in 0..130 {
+            board = board.next_state();
+        }
+        assert_eq!(0, board.population());
+    }
+
+    #[test]
+    pub fn test_search() {
+        let library = Library::built_in();
+        let names = |filter| {
+            library
+                .search(filter)
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec!["gosper glider gun"], names("GUN"));
+        assert_eq!(
+            vec!["middleweight spaceship", "heavyweight spaceship"],
+            names("spaceship longer")
+        );
+        assert_eq!(library.entries.len(), names("").len());
+    }
+
+    #[test]
+    pub fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("gol-library-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("spaceships")).unwrap();
+        std::fs::write(
+            dir.join("spaceships/glider.txt"),
+            "# goes diagonally\n010\n001\n111\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("dot.txt"), "1\n").unwrap();
+        std::fs::write(dir.join("broken.png"), "not a png").unwrap();
+
+        let mut library = Library {
+            entries: Vec::new(),
+        };
+        let skipped = library.load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let glider = library.get("glider").unwrap();
+        assert_eq!(Category::Spaceship, glider.category);
+        assert_eq!("goes diagonally", glider.description);
+        assert_eq!(5, glider.pattern.population());
+        assert_eq!(Category::Other, library.get("dot").unwrap().category);
+        assert_eq!(vec![dir.join("broken.png")], skipped);
+    }
+}
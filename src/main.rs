@@ -1,4 +1,11 @@
-fn main() -> Result<(), game_of_life::GOLError> {
-    // commands::run()
-    game_of_life::commands::run()
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match game_of_life::commands::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
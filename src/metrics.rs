@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::boards::{ant_board::Direction, Boards};
+
+/// Counts describing one generation of a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    pub generation: u64,
+    pub population: usize,
+    /// Cells that came alive since the previous generation
+    pub births: usize,
+    /// Cells that died since the previous generation
+    pub deaths: usize,
+    /// The smallest rectangle containing every live cell as `(left, top, right, bottom)`
+    pub bounding_box: Option<(usize, usize, usize, usize)>,
+    /// Where Langton's ant is and which way it is facing
+    pub ant: Option<(usize, usize, Direction)>,
+}
+
+/// Which cells of a board are alive, row by row, to compare generations with
+pub fn snapshot(board: &Boards) -> Vec<bool> {
+    let (width, height) = board.size();
+    let mut cells = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            cells.push(board.alive(x, y));
+        }
+    }
+
+    cells
+}
+
+impl Metrics {
+    /// Measures a board from its `cells`, as given by [`snapshot`], counting births and deaths
+    /// against the snapshot of the generation before
+    pub fn measure(
+        generation: u64,
+        board: &Boards,
+        cells: &[bool],
+        previous: Option<&[bool]>,
+    ) -> Metrics {
+        let (width, _) = board.size();
+
+        let mut metrics = Metrics {
+            generation,
+            population: 0,
+            births: 0,
+            deaths: 0,
+            bounding_box: None,
+            ant: None,
+        };
+
+        for (i, alive) in cells.iter().enumerate() {
+            let was_alive = previous.map_or(*alive, |previous| previous[i]);
+            match (was_alive, *alive) {
+                (false, true) => metrics.births += 1,
+                (true, false) => metrics.deaths += 1,
+                _ => {}
+            }
+
+            if *alive {
+                let (x, y) = (i % width, i / width);
+                metrics.population += 1;
+                metrics.bounding_box = Some(match metrics.bounding_box {
+                    None => (x, y, x, y),
+                    Some((left, top, right, bottom)) => {
+                        (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                    }
+                });
+            }
+        }
+
+        if let Boards::AntBoard(ant) = board {
+            metrics.ant = Some(ant.ant());
+        }
+
+        metrics
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+/// Streams metrics to a file, one line per generation. Lines are buffered and the rest are
+/// written out when the writer is dropped.
+pub struct MetricsWriter {
+    out: BufWriter<File>,
+    format: Format,
+}
+
+impl MetricsWriter {
+    /// Creates the file, writing JSON Lines if it ends in `.json` or `.jsonl` and CSV otherwise
+    pub fn create(path: &Path) -> io::Result<MetricsWriter> {
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") | Some("jsonl") => Format::JsonLines,
+            _ => Format::Csv,
+        };
+
+        let mut writer = MetricsWriter {
+            out: BufWriter::new(File::create(path)?),
+            format,
+        };
+
+        if format == Format::Csv {
+            writeln!(
+                writer.out,
+                "generation,population,births,deaths,left,top,right,bottom,ant_x,ant_y,ant_heading"
+            )?;
+        }
+
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, metrics: &Metrics) -> io::Result<()> {
+        let line = match self.format {
+            Format::Csv => csv(metrics),
+            Format::JsonLines => json(metrics),
+        };
+        writeln!(self.out, "{}", line)
+    }
+}
+
+fn csv(metrics: &Metrics) -> String {
+    let mut fields = vec![
+        metrics.generation.to_string(),
+        metrics.population.to_string(),
+        metrics.births.to_string(),
+        metrics.deaths.to_string(),
+    ];
+
+    match metrics.bounding_box {
+        Some((left, top, right, bottom)) => {
+            fields.extend([left, top, right, bottom].map(|bound| bound.to_string()))
+        }
+        None => fields.extend([""; 4].map(String::from)),
+    }
+
+    match metrics.ant {
+        Some((x, y, heading)) => {
+            fields.extend([x.to_string(), y.to_string(), heading.name().to_string()])
+        }
+        None => fields.extend([""; 3].map(String::from)),
+    }
+
+    fields.join(",")
+}
+
+fn json(metrics: &Metrics) -> String {
+    let mut json = format!(
+        "{{\"generation\":{},\"population\":{},\"births\":{},\"deaths\":{}",
+        metrics.generation, metrics.population, metrics.births, metrics.deaths
+    );
+
+    match metrics.bounding_box {
+        Some((left, top, right, bottom)) => json.push_str(&format!(
+            ",\"bounding_box\":{{\"left\":{},\"top\":{},\"right\":{},\"bottom\":{}}}",
+            left, top, right, bottom
+        )),
+        None => json.push_str(",\"bounding_box\":null"),
+    }
+
+    if let Some((x, y, heading)) = metrics.ant {
+        json.push_str(&format!(
+            ",\"ant\":{{\"x\":{},\"y\":{},\"heading\":\"{}\"}}",
+            x,
+            y,
+            heading.name()
+        ));
+    }
+
+    json.push('}');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv, json, snapshot, Metrics};
+    use crate::{
+        args::GOLMethod,
+        boards::{ant_board, gol_board, Boards},
+    };
+
+    #[test]
+    pub fn test_blinker_metrics() {
+        let mut board = gol_board::Board::dead_state(5, 5, GOLMethod::Normal);
+        for x in 1..4 {
+            board.set(x, 2, true);
+        }
+        let mut board = Boards::GolBoard(board);
+
+        let previous = snapshot(&board);
+        board.update();
+        let metrics = Metrics::measure(1, &board, &snapshot(&board), Some(&previous));
+
+        assert_eq!(3, metrics.population);
+        assert_eq!((2, 2), (metrics.births, metrics.deaths));
+        assert_eq!(Some((2, 1, 2, 3)), metrics.bounding_box);
+        assert_eq!("1,3,2,2,2,1,2,3,,,", csv(&metrics));
+        assert_eq!(
+            "{\"generation\":1,\"population\":3,\"births\":2,\"deaths\":2,\
+             \"bounding_box\":{\"left\":2,\"top\":1,\"right\":2,\"bottom\":3}}",
+            json(&metrics)
+        );
+    }
+
+    #[test]
+    pub fn test_ant_metrics() {
+        let board = Boards::AntBoard(ant_board::Board::create_board(5, 5));
+        let metrics = Metrics::measure(0, &board, &snapshot(&board), None);
+
+        assert_eq!(0, metrics.population);
+        assert_eq!(None, metrics.bounding_box);
+        assert_eq!("0,0,0,0,,,,,2,2,up", csv(&metrics));
+    }
+}
@@ -0,0 +1,457 @@
+use std::fmt;
+
+use crate::{
+    args::GOLMethod,
+    boards::{gol_board, Boards},
+    GOLError,
+};
+
+/// Generations a pattern is run for by [`Pattern::advance`] without it repeating before giving up
+const MAX_ADVANCE: u64 = 10_000;
+/// The most cells a pattern run by [`Pattern::advance`] can grow to cover, 200 by 200
+const MAX_ADVANCE_AREA: usize = 40_000;
+
+/// How a pasted pattern combines with the cells already on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteMode {
+    /// Live cells of the pattern are added to the board
+    #[default]
+    Or,
+    /// Live cells of the pattern flip the cells under them
+    Xor,
+    /// The whole rectangle of the pattern replaces the cells under it
+    Copy,
+}
+
+impl PasteMode {
+    pub fn next(self) -> PasteMode {
+        match self {
+            PasteMode::Or => PasteMode::Xor,
+            PasteMode::Xor => PasteMode::Copy,
+            PasteMode::Copy => PasteMode::Or,
+        }
+    }
+}
+
+impl fmt::Display for PasteMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasteMode::Or => write!(f, "or"),
+            PasteMode::Xor => write!(f, "xor"),
+            PasteMode::Copy => write!(f, "copy"),
+        }
+    }
+}
+
+/// A small rectangle of cells cut out of, or to be placed onto, a board
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Vec<bool>>,
+}
+
+impl Pattern {
+    pub fn empty(width: usize, height: usize) -> Pattern {
+        Pattern {
+            width,
+            height,
+            cells: vec![vec![false; width]; height],
+        }
+    }
+
+    /// Builds a pattern from rows where `O` (or `*`, `#`, `1`) is a live cell and anything else
+    /// is dead
+    pub fn from_rows(rows: &[&str]) -> Pattern {
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cells = rows
+            .iter()
+            .map(|row| {
+                let mut line: Vec<bool> = row
+                    .chars()
+                    .map(|c| matches!(c, 'O' | 'o' | '*' | '#' | '1'))
+                    .collect();
+                line.resize(width, false);
+                line
+            })
+            .collect();
+
+        Pattern {
+            width,
+            height: rows.len(),
+            cells,
+        }
+    }
+
+    /// Builds the smallest pattern containing the given live cells
+    pub fn from_points(points: &[(usize, usize)]) -> Pattern {
+        let left = points.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let top = points.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let right = points.iter().map(|(x, _)| *x + 1).max().unwrap_or(0);
+        let bottom = points.iter().map(|(_, y)| *y + 1).max().unwrap_or(0);
+
+        let mut pattern = Pattern::empty(right - left, bottom - top);
+        for (x, y) in points {
+            pattern.cells[y - top][x - left] = true;
+        }
+
+        pattern
+    }
+
+    /// Copies the live cells of a board, trimmed to their bounding box
+    pub fn from_board(board: &gol_board::Board) -> Pattern {
+        let mut points = Vec::new();
+        for y in 0..board.height {
+            for x in 0..board.width {
+                if board.get(x, y) {
+                    points.push((x, y));
+                }
+            }
+        }
+
+        Pattern::from_points(&points)
+    }
+
+    /// Copies a rectangle of a board, keeping its dead cells. The rectangle is cut short at the
+    /// edges of the board.
+    pub fn from_region(
+        board: &Boards,
+        left: usize,
+        top: usize,
+        width: usize,
+        height: usize,
+    ) -> Pattern {
+        let (board_width, board_height) = board.size();
+        let width = width.min(board_width.saturating_sub(left));
+        let height = height.min(board_height.saturating_sub(top));
+
+        let mut pattern = Pattern::empty(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                pattern.cells[y][x] = board.alive(left + x, top + y);
+            }
+        }
+
+        pattern
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.cells[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        self.cells[y][x] = alive;
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| **cell).count()
+    }
+
+    /// Draws the pattern as lines of `#` for live cells and spaces for dead ones, as boards are
+    pub fn render(&self) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| if self.get(x, y) { '#' } else { ' ' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Turns the pattern a quarter turn clockwise
+    pub fn rotate(&self) -> Pattern {
+        let mut rotated = Pattern::empty(self.height, self.width);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                rotated.cells[x][self.height - 1 - y] = self.cells[y][x];
+            }
+        }
+
+        rotated
+    }
+
+    /// Mirrors the pattern left to right
+    pub fn flip_horizontal(&self) -> Pattern {
+        let mut flipped = self.clone();
+        for line in flipped.cells.iter_mut() {
+            line.reverse();
+        }
+
+        flipped
+    }
+
+    /// Mirrors the pattern top to bottom
+    pub fn flip_vertical(&self) -> Pattern {
+        let mut flipped = self.clone();
+        flipped.cells.reverse();
+
+        flipped
+    }
+
+    /// The pattern in each of its eight rotations and reflections
+    pub fn orientations(&self) -> Vec<Pattern> {
+        let mut orientations = Vec::with_capacity(8);
+        let mut current = self.clone();
+        for _ in 0..4 {
+            orientations.push(current.flip_horizontal());
+            current = current.rotate();
+            orientations.push(current.clone());
+        }
+
+        orientations
+    }
+
+    /// Paints the live cells of the pattern onto a board with its drawing state, with the pattern's
+    /// top left corner at (left, top). Cells that would fall off the board are left out.
+    pub fn stamp(&self, board: &mut Boards, left: usize, top: usize) {
+        let (width, height) = board.size();
+        for y in 0..self.height.min(height.saturating_sub(top)) {
+            for x in 0..self.width.min(width.saturating_sub(left)) {
+                if self.cells[y][x] {
+                    board.draw_cell(left + x, top + y);
+                }
+            }
+        }
+    }
+
+    /// Pastes the pattern onto a board with its top left corner at (left, top), combining it with
+    /// the cells already there. Cells that would fall off the board are left out.
+    pub fn paste(&self, board: &mut Boards, left: usize, top: usize, mode: PasteMode) {
+        if mode == PasteMode::Or {
+            return self.stamp(board, left, top);
+        }
+
+        let (width, height) = board.size();
+        for y in 0..self.height.min(height.saturating_sub(top)) {
+            for x in 0..self.width.min(width.saturating_sub(left)) {
+                let (bx, by) = (left + x, top + y);
+                let alive = match mode {
+                    PasteMode::Xor => self.cells[y][x] != board.alive(bx, by),
+                    _ => self.cells[y][x],
+                };
+
+                if alive {
+                    board.draw_cell(bx, by);
+                } else {
+                    board.clear_cell(bx, by);
+                }
+            }
+        }
+    }
+
+    /// Writes the pattern in run length encoded form, as most Life programs read and write it
+    pub fn to_rle(&self) -> String {
+        let mut items = Vec::new();
+        // Line ends are only written before a line with live cells, leaving off blank lines at
+        // the bottom
+        let mut line_ends = 0;
+
+        for (y, line) in self.cells.iter().enumerate() {
+            if y > 0 {
+                line_ends += 1;
+            }
+
+            let end = line.iter().rposition(|cell| *cell).map_or(0, |x| x + 1);
+            if end == 0 {
+                continue;
+            }
+
+            if line_ends > 0 {
+                items.push(run(line_ends, '$'));
+                line_ends = 0;
+            }
+
+            let mut x = 0;
+            while x < end {
+                let length = line[x..end]
+                    .iter()
+                    .take_while(|cell| **cell == line[x])
+                    .count();
+                items.push(run(length, if line[x] { 'o' } else { 'b' }));
+                x += length;
+            }
+        }
+        items.push("!".to_string());
+
+        let mut rle = format!("x = {}, y = {}\n", self.width, self.height);
+        let mut line_length = 0;
+        for item in items {
+            if line_length + item.len() > 70 {
+                rle.push('\n');
+                line_length = 0;
+            }
+            line_length += item.len();
+            rle.push_str(&item);
+        }
+        rle.push('\n');
+
+        rle
+    }
+
+    /// The pattern `generations` generations later under B3/S23, trimmed to its live cells. Once
+    /// the pattern repeats, wherever it has moved to, the remaining whole periods are skipped. A
+    /// pattern that doesn't repeat within 10000 generations, or grows too large, is an error.
+    pub fn advance(&self, generations: u64) -> Result<Pattern, GOLError> {
+        // Brent's cycle detection: each phase is compared with one saved at the last power of two
+        let mut pattern = self.clone();
+        let mut saved = self.clone();
+        let (mut since_saved, mut power) = (0, 1);
+        for generation in 0..generations {
+            if generation == MAX_ADVANCE || pattern.width * pattern.height > MAX_ADVANCE_AREA {
+                return Err(GOLError::InvalidPattern(format!(
+                    "the pattern doesn't repeat by generation {}",
+                    generation
+                )));
+            }
+
+            pattern = pattern.step();
+            since_saved += 1;
+            if pattern == saved {
+                for _ in 0..(generations - generation - 1) % since_saved {
+                    pattern = pattern.step();
+                }
+                return Ok(pattern);
+            }
+            if since_saved == power {
+                saved = pattern.clone();
+                since_saved = 0;
+                power *= 2;
+            }
+        }
+
+        Ok(pattern)
+    }
+
+    /// The next generation under B3/S23, trimmed to its live cells
+    fn step(&self) -> Pattern {
+        // A pattern grows by at most one cell on each side every generation, so a margin of one
+        // is enough
+        Pattern::from_board(&self.to_board(1, GOLMethod::Normal).next_state())
+    }
+
+    /// Places the pattern on an empty board with `margin` dead cells on every side
+    pub fn to_board(&self, margin: usize, method: GOLMethod) -> gol_board::Board {
+        let mut board =
+            gol_board::Board::dead_state(self.width + 2 * margin, self.height + 2 * margin, method);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cells[y][x] {
+                    board.set(x + margin, y + margin, true);
+                }
+            }
+        }
+
+        board
+    }
+}
+
+/// A run of `length` cells or line ends, leaving out a count of 1
+fn run(length: usize, tag: char) -> String {
+    match length {
+        1 => tag.to_string(),
+        _ => format!("{}{}", length, tag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PasteMode, Pattern};
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, Boards},
+    };
+
+    #[test]
+    pub fn test_orientations() {
+        let glider = Pattern::from_rows(&[".O.", "..O", "OOO"]);
+
+        let mut orientations = glider.orientations();
+        orientations.sort();
+        orientations.dedup();
+
+        assert_eq!(8, orientations.len());
+        assert_eq!(glider, glider.rotate().rotate().rotate().rotate());
+        assert_eq!(
+            glider.flip_vertical(),
+            glider.rotate().rotate().flip_horizontal()
+        );
+    }
+
+    #[test]
+    pub fn test_rle() {
+        let glider = Pattern::from_rows(&[".O.", "..O", "OOO"]);
+        assert_eq!("x = 3, y = 3\nbo$2bo$3o!\n", glider.to_rle());
+
+        let gaps = Pattern::from_rows(&["O..", "...", "...", ".O.", "..."]);
+        assert_eq!("x = 3, y = 5\no3$bo!\n", gaps.to_rle());
+
+        let long = Pattern::from_rows(&["O.".repeat(40).as_str()]);
+        assert!(long.to_rle().lines().all(|line| line.len() <= 70));
+    }
+
+    #[test]
+    pub fn test_advance() {
+        let glider = Pattern::from_rows(&[".O.", "..O", "OOO"]);
+        assert_eq!(glider, glider.advance(4).unwrap());
+        assert_eq!(glider, glider.advance(400).unwrap());
+        assert_eq!(
+            glider.advance(2).unwrap(),
+            glider.advance(4_000_000_002).unwrap()
+        );
+        assert_eq!(
+            Pattern::from_rows(&["OOO"]),
+            Pattern::from_rows(&["O", "O", "O"]).advance(1).unwrap()
+        );
+
+        let block = Pattern::from_rows(&["OO", "OO"]);
+        assert_eq!(block, block.advance(u64::MAX).unwrap());
+        assert_eq!(
+            Pattern::empty(0, 0),
+            Pattern::from_rows(&["O"]).advance(u64::MAX).unwrap()
+        );
+
+        // Two gliders flying apart never repeat
+        let gliders = Pattern::from_rows(&[
+            "OOO......",
+            "O........",
+            ".O.......",
+            ".........",
+            ".........",
+            ".........",
+            ".......O.",
+            "........O",
+            "......OOO",
+        ]);
+        assert!(gliders.advance(u64::MAX).is_err());
+    }
+
+    #[test]
+    pub fn test_paste_modes() {
+        let board = || {
+            let mut board = gol_board::Board::dead_state(4, 1, GOLMethod::Normal);
+            board.set(0, 0, true);
+            board.set(1, 0, true);
+            Boards::GolBoard(board)
+        };
+        let pattern = Pattern::from_rows(&[".OO."]);
+
+        let pasted = |mode| {
+            let mut board = board();
+            pattern.paste(&mut board, 0, 0, mode);
+            Pattern::from_region(&board, 0, 0, 4, 1)
+        };
+
+        assert_eq!(Pattern::from_rows(&["OOO."]), pasted(PasteMode::Or));
+        assert_eq!(Pattern::from_rows(&["O.O."]), pasted(PasteMode::Xor));
+        assert_eq!(Pattern::from_rows(&[".OO."]), pasted(PasteMode::Copy));
+        assert_eq!(
+            Pattern::from_rows(&["OO"]),
+            Pattern::from_region(&board(), 0, 0, 2, 5)
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    analysis::apgcode,
+    boards::{gol_board, Boards},
+    import::{self, ImportOptions},
+    library::Library,
+    pattern::Pattern,
+    GOLError,
+};
+
+/// A change made to a pattern before it is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// A quarter turn clockwise
+    Rot90,
+    Rot180,
+    Rot270,
+    /// Mirror left to right
+    FlipX,
+    /// Mirror top to bottom
+    FlipY,
+    /// Run the pattern for this many generations
+    Gen(u64),
+}
+
+impl Transform {
+    pub fn apply(self, pattern: &Pattern) -> Result<Pattern, GOLError> {
+        match self {
+            Transform::Rot90 => Ok(pattern.rotate()),
+            Transform::Rot180 => Ok(pattern.rotate().rotate()),
+            Transform::Rot270 => Ok(pattern.rotate().rotate().rotate()),
+            Transform::FlipX => Ok(pattern.flip_horizontal()),
+            Transform::FlipY => Ok(pattern.flip_vertical()),
+            Transform::Gen(generations) => pattern.advance(generations),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = GOLError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rot90" => Ok(Transform::Rot90),
+            "rot180" => Ok(Transform::Rot180),
+            "rot270" => Ok(Transform::Rot270),
+            "flipx" => Ok(Transform::FlipX),
+            "flipy" => Ok(Transform::FlipY),
+            _ => s
+                .strip_prefix("gen")
+                .and_then(|generations| generations.parse().ok())
+                .map(Transform::Gen)
+                .ok_or_else(|| GOLError::InvalidPattern(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Transform::Rot90 => write!(f, "rot90"),
+            Transform::Rot180 => write!(f, "rot180"),
+            Transform::Rot270 => write!(f, "rot270"),
+            Transform::FlipX => write!(f, "flipx"),
+            Transform::FlipY => write!(f, "flipy"),
+            Transform::Gen(generations) => write!(f, "gen{}", generations),
+        }
+    }
+}
+
+/// A pattern to place on a board, written `source@x,y` followed by any transforms, e.g.
+/// `glider@10,5:rot90:gen2`. The source is the name of a pattern in the library, an apgcode or a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub source: String,
+    /// Where the top left corner of the pattern's live cells goes
+    pub position: (usize, usize),
+    /// Applied in order
+    pub transforms: Vec<Transform>,
+}
+
+impl Placement {
+    /// Parses a placement, giving the offset in characters of the part that is wrong on failure
+    fn parse(s: &str) -> Result<Placement, (usize, GOLError)> {
+        // Offsets are counted in bytes while parsing and turned into characters on failure
+        let invalid = |offset: usize| {
+            (
+                s[..offset].chars().count(),
+                GOLError::InvalidPattern(s.trim().to_string()),
+            )
+        };
+        let indent = |part: &str| part.len() - part.trim_start().len();
+
+        let start = indent(s);
+        let trimmed = s.trim();
+        let at = trimmed.rfind('@').ok_or_else(|| invalid(start))?;
+        let source = &trimmed[..at];
+
+        let rest = start + at + 1;
+        let mut offset = rest;
+        let mut parts = trimmed[at + 1..].split(':').map(|part| {
+            let part_offset = offset;
+            offset += part.len() + 1;
+            (part_offset, part)
+        });
+
+        let (position_offset, position) = parts.next().unwrap_or((rest, ""));
+        let (x, y) = position
+            .split_once(',')
+            .ok_or_else(|| invalid(position_offset))?;
+        let y_offset = position_offset + x.len() + 1;
+        let position = (
+            x.trim()
+                .parse()
+                .map_err(|_| invalid(position_offset + indent(x)))?,
+            y.trim()
+                .parse()
+                .map_err(|_| invalid(y_offset + indent(y)))?,
+        );
+
+        let mut transforms = Vec::new();
+        for (part_offset, part) in parts {
+            transforms.push(part.parse().map_err(|err| (invalid(part_offset).0, err))?);
+        }
+
+        Ok(Placement {
+            source: source.trim().to_string(),
+            position,
+            transforms,
+        })
+    }
+}
+
+impl FromStr for Placement {
+    type Err = GOLError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Placement::parse(s).map_err(|(_, err)| err)
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{},{}", self.source, self.position.0, self.position.1)?;
+        for transform in &self.transforms {
+            write!(f, ":{}", transform)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the pattern a source names: one from the library such as `glider` or `long-boat`, an
+/// apgcode, or a board or image file. Files are looked for relative to `dir`.
+pub fn resolve(source: &str, dir: &Path, library: &Library) -> Result<Pattern, GOLError> {
+    if let Some(entry) = library.get(source) {
+        return Ok(entry.pattern.clone());
+    }
+
+    let path = dir.join(source);
+    if !path.exists() && source.starts_with('x') {
+        return apgcode::decode(source);
+    }
+
+    if import::is_image(&path) {
+        return import::load_image(&path, &ImportOptions::default())
+            .map_err(|err| GOLError::io(&path, err));
+    }
+
+    match gol_board::Board::load_from_file(&path, false) {
+        Ok(board) => Ok(Pattern::from_board(&board)),
+        Err(GOLError::Io { source: err, .. }) if err.kind() == io::ErrorKind::NotFound => {
+            Err(GOLError::InvalidPattern(source.to_string()))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Patterns to compose a board from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scene {
+    pub placements: Vec<Placement>,
+    /// The directory files named in the placements are relative to
+    pub dir: PathBuf,
+}
+
+impl Scene {
+    /// Loads a scene file: one placement per line, with blank lines and lines starting with `#`
+    /// skipped. Files named in it are relative to the scene file.
+    pub fn load_from_file(path: &Path) -> Result<Scene, GOLError> {
+        let file = std::fs::read_to_string(path).map_err(|err| GOLError::io(path, err))?;
+        let mut placements = Vec::new();
+
+        for (number, line) in file.lines().enumerate() {
+            let placement = line.trim();
+            if placement.is_empty() || placement.starts_with('#') {
+                continue;
+            }
+
+            placements.push(
+                Placement::parse(line).map_err(|(offset, err)| GOLError::Parse {
+                    path: path.to_path_buf(),
+                    line: number + 1,
+                    column: offset + 1,
+                    message: err.to_string(),
+                })?,
+            );
+        }
+
+        Ok(Scene {
+            placements,
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        })
+    }
+
+    /// Stamps every placement onto the board, finding the patterns named in the library
+    pub fn apply(&self, board: &mut Boards, library: &Library) -> Result<(), GOLError> {
+        for placement in &self.placements {
+            let pattern = placement.transforms.iter().try_fold(
+                resolve(&placement.source, &self.dir, library)?,
+                |pattern, transform| transform.apply(&pattern),
+            )?;
+
+            pattern.stamp(board, placement.position.0, placement.position.1);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{resolve, Placement, Scene, Transform};
+    use crate::{
+        args::GOLMethod,
+        boards::{gol_board, Boards},
+        library::Library,
+        pattern::Pattern,
+        GOLError,
+    };
+
+    #[test]
+    pub fn test_parse_placement() {
+        let placement: Placement = "glider@10,5:rot90:flipx:gen4".parse().unwrap();
+
+        assert_eq!("glider", placement.source);
+        assert_eq!((10, 5), placement.position);
+        assert_eq!(
+            vec![Transform::Rot90, Transform::FlipX, Transform::Gen(4)],
+            placement.transforms
+        );
+        assert_eq!("glider@10,5:rot90:flipx:gen4", placement.to_string());
+        assert!("glider@10".parse::<Placement>().is_err());
+        assert!("glider@1,2:spin".parse::<Placement>().is_err());
+    }
+
+    #[test]
+    pub fn test_apply() {
+        let scene = Scene {
+            placements: vec![
+                "block@0,0".parse().unwrap(),
+                "xp2_7@5,1".parse().unwrap(),
+                "glider@8,8:gen4".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let mut board = Boards::GolBoard(gol_board::Board::dead_state(12, 12, GOLMethod::Normal));
+
+        scene.apply(&mut board, &Library::built_in()).unwrap();
+
+        let Boards::GolBoard(board) = board else {
+            unreachable!()
+        };
+        assert_eq!(4 + 3 + 5, board.population());
+        assert!(board.get(5, 1) && board.get(5, 3));
+        // After a full period the glider has moved one cell down and right of where it started
+        assert_eq!(
+            Pattern::from_rows(&[".O.", "..O", "OOO"]),
+            Pattern::from_points(
+                &(8..12)
+                    .flat_map(|y| (8..12).map(move |x| (x, y)))
+                    .filter(|(x, y)| board.get(*x, *y))
+                    .collect::<Vec<_>>()
+            )
+        );
+        let library = Library::built_in();
+        assert!(resolve("long-boat", Path::new("."), &library).is_ok());
+        assert!(matches!(
+            resolve("no such pattern", Path::new("."), &library),
+            Err(GOLError::InvalidPattern(_))
+        ));
+        // Files that are there but can't be read keep their reason
+        assert!(matches!(
+            resolve("src", Path::new("."), &library),
+            Err(GOLError::Io { .. })
+        ));
+    }
+
+    #[test]
+    pub fn test_load_errors() {
+        let path = std::env::temp_dir().join(format!("gol-scene-{}.txt", std::process::id()));
+        for (scene, at) in [
+            ("# two gliders\nglider@0,0\n\n  glider@5\n", (4, 10)),
+            ("glider@0,0\n  glider\n", (2, 3)),
+            ("glider@0, x1\n", (1, 11)),
+            ("glider@1,2:flipx:spin\n", (1, 18)),
+            // Columns count characters rather than bytes
+            ("glidér@0,x\n", (1, 10)),
+        ] {
+            std::fs::write(&path, scene).unwrap();
+            match Scene::load_from_file(&path) {
+                Err(GOLError::Parse { line, column, .. }) => assert_eq!(at, (line, column)),
+                other => panic!("{:?}", other),
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            Scene::load_from_file(&path),
+            Err(GOLError::Io { .. })
+        ));
+    }
+}
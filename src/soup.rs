@@ -0,0 +1,172 @@
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::boards::Boards;
+
+/// The part of a board filled by a soup, centered unless a position is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub width: usize,
+    pub height: usize,
+    /// The top left cell of the region
+    pub position: Option<(usize, usize)>,
+}
+
+impl Region {
+    /// The columns and rows of a board of the given size covered by the region
+    pub fn bounds(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let (left, top) = self.position.unwrap_or((
+            width.saturating_sub(self.width) / 2,
+            height.saturating_sub(self.height) / 2,
+        ));
+        let left = left.min(width);
+        let top = top.min(height);
+
+        (
+            left..(left + self.width).min(width),
+            top..(top + self.height).min(height),
+        )
+    }
+}
+
+/// Parses `WxH`, or `WxH+X+Y` to place the region's top left cell at (X, Y)
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{} is not a region such as 16x16 or 16x16+4+4", s);
+        let mut parts = s.split('+');
+
+        let (width, height) = parts
+            .next()
+            .and_then(|size| size.split_once('x'))
+            .ok_or_else(invalid)?;
+        let width = width.parse().map_err(|_| invalid())?;
+        let height = height.parse().map_err(|_| invalid())?;
+
+        let position = match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => None,
+            (Some(x), Some(y), None) => Some((
+                x.parse().map_err(|_| invalid())?,
+                y.parse().map_err(|_| invalid())?,
+            )),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Region {
+            width,
+            height,
+            position,
+        })
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)?;
+        if let Some((x, y)) = self.position {
+            write!(f, "+{}+{}", x, y)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The settings a random board is made from. The same soup on the same size of board always
+/// gives the same cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soup {
+    pub seed: u64,
+    /// The chance of each cell in the region being alive
+    pub density: f64,
+    /// The part of the board to fill, all of it if not given
+    pub region: Option<Region>,
+}
+
+impl Soup {
+    /// A soup from the given seed, or from a random one
+    pub fn new(seed: Option<u64>, density: f64, region: Option<Region>) -> Soup {
+        Soup {
+            seed: seed.unwrap_or_else(rand::random),
+            density,
+            region,
+        }
+    }
+
+    /// The live cells of the soup on a board of the given size
+    pub fn cells(&self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let (columns, rows) = match self.region {
+            Some(region) => region.bounds(width, height),
+            None => (0..width, 0..height),
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut cells = Vec::new();
+        for y in rows {
+            for x in columns.clone() {
+                if rng.gen_bool(self.density) {
+                    cells.push((x, y));
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Paints the live cells of the soup onto a board with its drawing state
+    pub fn fill(&self, board: &mut Boards) {
+        let (width, height) = board.size();
+        for (x, y) in self.cells(width, height) {
+            board.draw_cell(x, y);
+        }
+    }
+}
+
+impl fmt::Display for Soup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "seed {}, density {}", self.seed, self.density)?;
+        if let Some(region) = self.region {
+            write!(f, ", region {}", region)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Region, Soup};
+
+    #[test]
+    pub fn test_region() {
+        let centered: Region = "4x2".parse().unwrap();
+        let placed: Region = "4x2+1+3".parse().unwrap();
+
+        assert_eq!((3..7, 4..6), centered.bounds(10, 10));
+        assert_eq!((1..5, 3..5), placed.bounds(10, 10));
+        assert_eq!(
+            (8..10, 3..5),
+            "4x2+8+3".parse::<Region>().unwrap().bounds(10, 10)
+        );
+        assert_eq!("4x2+1+3", placed.to_string());
+        assert!("4x".parse::<Region>().is_err());
+        assert!("4x2+1".parse::<Region>().is_err());
+    }
+
+    #[test]
+    pub fn test_reproducible() {
+        let soup = Soup::new(Some(42), 0.3, "16x16".parse().ok());
+        let cells = soup.cells(40, 30);
+
+        assert_eq!(cells, soup.cells(40, 30));
+        assert_ne!(cells, Soup { seed: 43, ..soup }.cells(40, 30));
+        assert!(cells
+            .iter()
+            .all(|(x, y)| (12..28).contains(x) && (7..23).contains(y)));
+    }
+}
@@ -1,83 +1,212 @@
-use std::{io, time::Duration};
+use std::{
+    collections::HashMap,
+    io,
+    panic::PanicHookInfo,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crossterm::{
+    cursor::Show,
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use tui::{
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Borders, Gauge, List, ListItem, ListState, Paragraph},
     Frame, Terminal,
 };
 
 use crate::{
-    args::GOLMethod,
-    boards::{self, Boards},
-    game::Game,
+    clock::Clock,
+    config::{Config, Theme},
+    game::{Browser, Game},
     inputs::{events::Events, InputEvent, Key},
+    keymap::{Action, Keymap, QUIT_KEY},
+    library::Library,
 };
 
 use crate::GOLError;
 
-pub fn start_ui(game: &mut Game, sleep_time: u64) -> Result<(), GOLError> {
-    enable_raw_mode().unwrap();
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen).unwrap();
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).unwrap();
+/// The look and keys of the terminal UI, read from the config before any board is built
+pub struct Ui {
+    theme: Theme,
+    keymap: Keymap,
+}
+
+impl Ui {
+    pub fn new(config: &Config) -> Result<Ui, GOLError> {
+        Ok(Ui {
+            theme: config.theme,
+            keymap: config.keymap()?,
+        })
+    }
+}
+
+/// Runs the game in the terminal. Generations are run `sleep_time` milliseconds apart and the
+/// board is drawn `fps` times a second, with keys handled as soon as they are pressed.
+pub fn start_ui(game: &mut Game, ui: &Ui, sleep_time: u64, fps: u32) -> Result<(), GOLError> {
+    let Ui { theme, keymap } = ui;
+
+    let _guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend).map_err(GOLError::Terminal)?;
 
-    let tick_rate = Duration::from_millis(if sleep_time < 10 { 10 } else { sleep_time });
-    let events = Events::new(tick_rate);
+    let frame = Duration::from_secs(1) / fps.max(1);
+    let mut clock = Clock::new(Duration::from_millis(sleep_time), Instant::now());
+    let mut next_frame = Instant::now();
+    let events = Events::new();
+
+    let (library, skipped) = Library::load();
+    game.library = library;
+    if !skipped.is_empty() {
+        game.message = Some(format!("couldn't load {} user patterns", skipped.len()));
+    }
 
     loop {
-        terminal.draw(|pg| draw(pg, &game)).unwrap();
+        let now = Instant::now();
+        if game.fast_forward.is_some() {
+            game.run_fast_forward(now + frame);
+            clock.reset(now);
+        } else if game.paused {
+            clock.reset(now);
+        } else {
+            run(game, &mut clock, now + frame / 2);
+        }
 
-        let result = match events.next().unwrap_or(InputEvent::Tick) {
-            InputEvent::Input(key) => process_key(key),
-            InputEvent::Tick => update(game),
+        let now = Instant::now();
+        if now >= next_frame {
+            terminal
+                .draw(|pg| draw(pg, game, &clock, keymap, theme))
+                .map_err(GOLError::Terminal)?;
+            next_frame = now + frame;
+        }
+
+        let wake = match (game.fast_forward, game.paused) {
+            (Some(_), _) => now,
+            (None, true) => next_frame,
+            (None, false) => next_frame.min(clock.next()),
+        };
+        let result = match events.next(wake.saturating_duration_since(Instant::now())) {
+            InputEvent::Input(key) => process_key(key, game, &mut clock, keymap),
+            InputEvent::Tick => Return::Continue,
+            InputEvent::Error(err) => return Err(GOLError::Terminal(err)),
         };
 
         if result == Return::Exit {
-            break;
+            return Ok(());
         }
     }
+}
 
-    disable_raw_mode().unwrap();
+/// Raw mode and the alternate screen, which are left however the UI ends, even by panicking
+struct TerminalGuard {
+    /// The panic hook from before the UI started, put back once it ends
+    hook: Arc<PanicHook>,
+}
 
-    execute!(terminal.backend_mut(), LeaveAlternateScreen).unwrap();
+type PanicHook = Box<dyn Fn(&PanicHookInfo) + Sync + Send + 'static>;
 
-    terminal.show_cursor().unwrap();
+impl TerminalGuard {
+    fn enter() -> Result<TerminalGuard, GOLError> {
+        enable_raw_mode().map_err(GOLError::Terminal)?;
+        let guard = TerminalGuard {
+            hook: Arc::new(std::panic::take_hook()),
+        };
+        execute!(io::stdout(), EnterAlternateScreen).map_err(GOLError::Terminal)?;
+
+        // The terminal is restored before a panic's message is printed, so that it can be read
+        let hook = guard.hook.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            hook(info);
+        }));
 
-    Ok(())
+        Ok(guard)
+    }
 }
 
-fn draw<B>(item: &mut Frame<B>, game: &Game)
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        // The hook can't be changed while panicking, and it restores the terminal harmlessly
+        if !std::thread::panicking() {
+            let hook = self.hook.clone();
+            std::panic::set_hook(Box::new(move |info| hook(info)));
+        }
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+}
+
+/// Runs the generations that are due, giving up on the rest at the deadline so that keys and
+/// frames aren't held up
+fn run(game: &mut Game, clock: &mut Clock, deadline: Instant) {
+    let due = clock.due(Instant::now());
+
+    let mut ran = 0;
+    while ran < due && Instant::now() < deadline {
+        game.update();
+        ran += 1;
+    }
+}
+
+fn draw<B>(item: &mut Frame<B>, game: &Game, clock: &Clock, keymap: &Keymap, theme: &Theme)
 where
     B: Backend,
 {
     let size = item.size();
 
-    check_size(&size, game);
+    if let Some(message) = check_size(&size, game) {
+        item.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(theme.status.0))
+                .alignment(Alignment::Center),
+            size,
+        );
+        return;
+    }
 
-    let title = draw_title();
+    let title = draw_title(game, theme);
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(10)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Min(10),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
         .split(size);
 
-    let bod = draw_body(game);
+    let status = draw_status(game, clock, theme);
 
     item.render_widget(title, chunks[0]);
 
-    item.render_widget(bod, chunks[1]);
+    if game.fast_forward.is_some() {
+        draw_progress(item, chunks[1], game, theme);
+    } else if game.help {
+        draw_help(item, chunks[1], keymap);
+    } else if game.browser.is_some() {
+        draw_library(item, chunks[1], game);
+    } else {
+        item.render_widget(draw_body(game, theme), chunks[1]);
+    }
+
+    item.render_widget(status, chunks[2]);
 }
 
-fn draw_title<'a>() -> Paragraph<'a> {
-    Paragraph::new("Conway's Game of Life")
-        .style(Style::default().fg(Color::LightCyan))
+fn draw_title<'a>(game: &Game, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(game.board.name())
+        .style(Style::default().fg(theme.title.0))
         .alignment(Alignment::Center)
         .block(
             Block::default()
@@ -87,12 +216,264 @@ fn draw_title<'a>() -> Paragraph<'a> {
         )
 }
 
-fn draw_body<'a>(game: &Game) -> Paragraph<'a> {
-    match &game.board {
-        boards::Boards::AntBoard(ant) => Paragraph::new(ant.render()),
-        boards::Boards::GolBoard(gol) => Paragraph::new(gol.render()),
+fn draw_body<'a>(game: &Game, theme: &Theme) -> Paragraph<'a> {
+    let rendered = render(game, theme);
+
+    // The screen positions of the selected cells and of the cursor, with how they are shown
+    let mut highlighted = HashMap::new();
+    if let Some((left, top, width, height)) = game.selected_region() {
+        for y in top..top + height {
+            for x in left..left + width {
+                highlighted.insert(
+                    game.board.screen_position(x, y),
+                    Style::default().bg(theme.selection.0),
+                );
+            }
+        }
+    }
+    if game.paused || game.selection.is_some() {
+        highlighted.insert(
+            game.board.screen_position(game.cursor.0, game.cursor.1),
+            Style::default().add_modifier(Modifier::REVERSED),
+        );
+    }
+
+    let lines: Vec<Spans> = rendered
+        .lines()
+        .enumerate()
+        .map(|(y, line)| {
+            if !highlighted.keys().any(|(_, line)| *line == y) {
+                return Spans::from(line.to_string());
+            }
+
+            Spans::from(
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| match highlighted.get(&(x, y)) {
+                        Some(style) => Span::styled(c.to_string(), *style),
+                        None => Span::raw(c.to_string()),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    Paragraph::new(Text::from(lines))
+        .style(Style::default().fg(theme.board.0))
+        .alignment(Alignment::Center)
+}
+
+/// The board as text, with its live and dead cells drawn with the glyphs of the theme
+fn render(game: &Game, theme: &Theme) -> String {
+    let rendered = game.board.render();
+    if (theme.alive, theme.dead) == ('#', ' ') {
+        // The boards' own glyphs, dots for dead hexagonal cells included
+        return rendered;
+    }
+
+    let mut lines: Vec<Vec<char>> = rendered
+        .lines()
+        .map(|line| line.chars().collect())
+        .collect();
+    let (width, height) = game.board.size();
+    for y in 0..height {
+        for x in 0..width {
+            let (column, line) = game.board.screen_position(x, y);
+            let Some(c) = lines.get_mut(line).and_then(|line| line.get_mut(column)) else {
+                continue;
+            };
+
+            match game.board.live_glyph(x, y) {
+                Some(true) => *c = theme.alive,
+                Some(false) => *c = theme.dead,
+                None => {}
+            }
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How far a fast-forward has got, in place of the board
+fn draw_progress<B>(item: &mut Frame<B>, area: Rect, game: &Game, theme: &Theme)
+where
+    B: Backend,
+{
+    let Some((from, to)) = game.fast_forward else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("fast-forwarding (esc to cancel)"),
+        )
+        .gauge_style(Style::default().fg(theme.title.0))
+        .ratio((game.generation - from) as f64 / (to - from) as f64)
+        .label(format!("generation {} of {}", game.generation, to));
+
+    item.render_widget(gauge, chunks[1]);
+}
+
+/// Every action with its keys, in two columns
+fn draw_help<B>(item: &mut Frame<B>, area: Rect, keymap: &Keymap)
+where
+    B: Backend,
+{
+    let lines: Vec<String> = keymap
+        .bindings()
+        .iter()
+        .map(|(action, keys)| {
+            let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+            format!("{:>24}  {}", keys.join(" "), action.description())
+        })
+        .collect();
+    let (left, right) = lines.split_at(lines.len().div_ceil(2));
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    item.render_widget(
+        Paragraph::new(left.join("\n")).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("keys (any key to close)"),
+        ),
+        chunks[0],
+    );
+    item.render_widget(
+        Paragraph::new(right.join("\n")).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+}
+
+/// The pattern library panel: the patterns matching the filter on the left and a preview of the
+/// selected one on the right
+fn draw_library<B>(item: &mut Frame<B>, area: Rect, game: &Game)
+where
+    B: Backend,
+{
+    let Some(browser) = &game.browser else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(area);
+
+    let items: Vec<ListItem> = game
+        .library
+        .search(&browser.filter)
+        .map(|entry| ListItem::new(format!("{} ({})", entry.name, entry.category)))
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("library: {}_", browser.filter)),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default();
+    state.select(Some(browser.selected));
+    item.render_stateful_widget(list, chunks[0], &mut state);
+
+    let preview = match game.selected_entry() {
+        Some(entry) => format!(
+            "{}\n{}\n{}x{}, {} cells\n\n{}",
+            entry.name,
+            entry.description,
+            entry.pattern.width,
+            entry.pattern.height,
+            entry.pattern.population(),
+            entry.pattern.render()
+        ),
+        None => "no patterns match".to_string(),
+    };
+
+    item.render_widget(
+        Paragraph::new(preview).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("enter: load | tab: copy | esc: close"),
+        ),
+        chunks[1],
+    );
+}
+
+fn draw_status<'a>(game: &Game, clock: &Clock, theme: &Theme) -> Paragraph<'a> {
+    let mut status = format!("generation {} | {}", game.generation, clock);
+
+    if let Some(soup) = game.soup {
+        status.push_str(&format!(" | seed {}", soup.seed));
+    }
+
+    if let Some(cycle) = game.cycle() {
+        status.push_str(&format!(" | {}", cycle));
     }
-    .alignment(Alignment::Center)
+
+    if game.paused {
+        status.push_str(&format!(
+            " | paused | cursor {},{}",
+            game.cursor.0, game.cursor.1
+        ));
+    }
+
+    if game.reversed {
+        status.push_str(" | reversed");
+    }
+
+    if game.drawing {
+        status.push_str(" | drawing");
+    }
+
+    if let Some((_, _, width, height)) = game.selected_region() {
+        status.push_str(&format!(" | selecting {}x{}", width, height));
+    }
+
+    if game.paused || game.drawing {
+        status.push_str(&format!(
+            " | clipboard {} ({})",
+            game.clipboard.0, game.paste_mode
+        ));
+    }
+
+    if game.browser.is_some() {
+        status.push_str(" | library");
+    }
+
+    if let Some(goto) = &game.goto {
+        status.push_str(&format!(" | go to generation: {}_", goto));
+    }
+
+    if let Some(message) = &game.message {
+        status.push_str(&format!(" | {}", message));
+    }
+
+    Paragraph::new(status)
+        .style(Style::default().fg(theme.status.0))
+        .alignment(Alignment::Center)
 }
 
 #[derive(PartialEq, Eq)]
@@ -101,43 +482,166 @@ pub enum Return {
     Continue,
 }
 
-fn process_key(key: Key) -> Return {
-    if key == Key::Ctrl('c') || key == Key::Char('q') {
+fn process_key(key: Key, game: &mut Game, clock: &mut Clock, keymap: &Keymap) -> Return {
+    game.message = None;
+
+    if key == QUIT_KEY {
         return Return::Exit;
     }
+
+    if game.help {
+        game.help = false;
+        return Return::Continue;
+    }
+
+    if game.fast_forward.is_some() {
+        match (key, keymap.action(key)) {
+            (Key::Esc, _) => game.cancel_fast_forward(),
+            (_, Some(Action::Quit)) => return Return::Exit,
+            _ => {}
+        }
+        return Return::Continue;
+    }
+
+    if game.browser.is_some() {
+        process_library_key(key, game);
+        return Return::Continue;
+    }
+
+    if game.goto.is_some() {
+        process_goto_key(key, game);
+        return Return::Continue;
+    }
+
+    let Some(action) = keymap.action(key) else {
+        return Return::Continue;
+    };
+
+    match action {
+        Action::Quit => return Return::Exit,
+        Action::Pause => game.paused = !game.paused,
+        Action::Step if game.paused => game.update(),
+        Action::StepBack if game.paused => game.rewind(),
+        Action::Step | Action::StepBack => {}
+        Action::Reverse => game.reversed = !game.reversed,
+        Action::Faster => clock.faster(),
+        Action::Slower => clock.slower(),
+        Action::Goto => game.goto = Some(String::new()),
+        Action::Draw => {
+            game.drawing = !game.drawing;
+            if game.drawing {
+                game.draw_cell();
+            }
+        }
+        Action::DrawCell => game.draw_cell(),
+        Action::ToggleCell => game.cycle_cell(),
+        Action::CursorLeft => game.move_cursor(-1, 0),
+        Action::CursorRight => game.move_cursor(1, 0),
+        Action::CursorUp => game.move_cursor(0, -1),
+        Action::CursorDown => game.move_cursor(0, 1),
+        Action::Select => game.toggle_selection(),
+        Action::Copy => game.copy(),
+        Action::Cut => game.cut(),
+        Action::Paste => game.paste(),
+        Action::PasteMode => game.paste_mode = game.paste_mode.next(),
+        Action::NextPattern => game.next_pattern(),
+        Action::Rotate => game.rotate_clipboard(),
+        Action::FlipHorizontal => game.flip_clipboard(true),
+        Action::FlipVertical => game.flip_clipboard(false),
+        Action::Library => game.browser = Some(Browser::default()),
+        Action::Undo => game.undo(),
+        Action::Redo => game.redo(),
+        Action::Clear => game.clear(),
+        Action::Randomize => game.randomize(),
+        Action::Save => game.save(),
+        Action::ExportPng => game.snapshot("png"),
+        Action::ExportSvg => game.snapshot("svg"),
+        Action::ExportRle => game.export_rle(),
+        Action::Help => game.help = true,
+    }
     Return::Continue
 }
 
-fn update(game: &mut Game) -> Return {
-    match &mut game.board {
-        Boards::GolBoard(board) => match board.method {
-            GOLMethod::Normal => *board = board.next_state(),
-            GOLMethod::VonNeumann => *board = board.next_state_neumann(),
-            GOLMethod::Dan => *board = board.next_state_dan(),
-        },
-        Boards::AntBoard(board) => *board = board.update(),
+/// Digits typed after the go to key make up the generation to run to, or how many generations
+/// to run after a `+`. Enter starts running.
+fn process_goto_key(key: Key, game: &mut Game) {
+    let Some(goto) = &mut game.goto else {
+        return;
     };
 
-    Return::Continue
+    match key {
+        Key::Esc => game.goto = None,
+        Key::Enter => {
+            let goto = std::mem::take(goto);
+            game.goto = None;
+            let target = match goto.strip_prefix('+') {
+                Some(count) => count
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|count| game.generation.checked_add(count)),
+                None => goto.parse().ok(),
+            };
+
+            match target {
+                Some(target) if !game.fast_forward(target) => {
+                    game.message = Some(format!("already at generation {}", game.generation));
+                }
+                Some(_) => {}
+                None if goto.trim_start_matches('+').is_empty() => {}
+                None => game.message = Some(format!("can't go to generation {}", goto)),
+            }
+        }
+        Key::Backspace => {
+            goto.pop();
+        }
+        Key::Char('+') if goto.is_empty() => goto.push('+'),
+        Key::Char(c) if c.is_ascii_digit() => goto.push(c),
+        _ => {}
+    }
 }
 
-fn check_size(rect: &Rect, game: &Game) {
-    let (width, height) = match &game.board {
-        boards::Boards::AntBoard(ant) => (ant.width, ant.height),
-        boards::Boards::GolBoard(gol) => (gol.width, gol.height),
+/// Keys typed while the pattern library is open filter it, apart from those that move the
+/// selection or act on it
+fn process_library_key(key: Key, game: &mut Game) {
+    let matching = game
+        .browser
+        .as_ref()
+        .map_or(0, |browser| game.library.search(&browser.filter).count());
+    let Some(browser) = &mut game.browser else {
+        return;
     };
 
-    if (rect.width as usize) < width + 5 {
-        panic!(
-            "Require width >= to the board width + 5, (board {}, terminal {})",
-            width, rect.width
-        );
+    match key {
+        Key::Esc => game.browser = None,
+        Key::Enter => game.load_selected(),
+        Key::Tab => game.copy_selected(),
+        Key::Up => browser.selected = browser.selected.saturating_sub(1),
+        Key::Down => browser.selected = (browser.selected + 1).min(matching.saturating_sub(1)),
+        Key::Backspace => {
+            browser.filter.pop();
+            browser.selected = 0;
+        }
+        Key::Char(c) => {
+            browser.filter.push(c);
+            browser.selected = 0;
+        }
+        _ => {}
     }
+}
 
-    if (rect.height as usize) < height + 5 {
-        panic!(
-            "Require height >= to the board height + 5, (board {}, terminal {})",
-            height, rect.height
-        );
+/// What's wrong when the terminal is too small to show the board
+fn check_size(rect: &Rect, game: &Game) -> Option<String> {
+    let (width, height) = game.board.screen_size();
+
+    if (rect.width as usize) < width + 5 || (rect.height as usize) < height + 6 {
+        return Some(format!(
+            "The terminal is too small for the board: it needs to be at least {}x{} and is {}x{}",
+            width + 5,
+            height + 6,
+            rect.width,
+            rect.height
+        ));
     }
+
+    None
 }